@@ -1,7 +1,12 @@
 use std::sync::Arc;
+use std::pin::Pin;
 use std::hash::Hash;
+use std::iter::FromIterator;
 use std::collections::HashSet;
 
+use crate::backend::SetBackend;
+use crate::refcount_interner::{RefCountInterner, FrozenRefCountInterner};
+
 /// An interner returning atomically reference-counted pointers to the interned
 /// data
 ///
@@ -22,284 +27,412 @@ use std::collections::HashSet;
 /// assert_ne!(x, y);
 /// assert!(Arc::ptr_eq(&x, &interner.intern(42)));
 /// ```
-#[derive(Debug)]
-pub struct ArcInterner<T: ?Sized>(HashSet<Arc<T>>);
-
-impl<T: ?Sized> Default for ArcInterner<T> {
-    fn default() -> ArcInterner<T> {
-        ArcInterner(HashSet::new())
-    }
-}
-
-impl<T: ?Sized + Hash + Eq> ArcInterner<T> {
-    /// Create a new, empty interner.
-    ///
-    /// # Example
-    /// ```rust
-    /// # use refcount_interner::ArcInterner;
-    /// let mut interner = ArcInterner::new();
-    /// # let x = interner.intern(42);
-    /// ```
-    pub fn new() -> ArcInterner<T> {
-        Default::default()
-    }
+///
+/// The interned objects are stored in a `HashSet` by default. A custom hasher
+/// can be used with `with_hasher()`. Other sets can be used by implementing
+/// `SetBackend` for them, see `with_backend()`.
+///
+/// This is `RefCountInterner` returning `Arc<T>` handles, see there for all
+/// methods that are available on both `RcInterner` and `ArcInterner`.
+///
+/// # Examples of the shared methods
+///
+/// The methods of `RefCountInterner` work the same way on `ArcInterner`:
+///
+/// `new()`:
+/// ```rust
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+/// # let x = interner.intern(42);
+/// ```
+///
+/// `try_intern()`:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let x = interner.intern(42);
+/// assert_eq!(interner.try_intern(&42), Some(Arc::new(42)));
+/// assert_eq!(interner.try_intern(&1337), None);
+/// ```
+///
+/// `intern_boxed()`:
+/// ```rust
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let x = Box::new(42);
+/// let y = interner.intern_boxed(x);
+///
+/// assert_eq!(*y, 42);
+/// ```
+///
+/// `shrink_to_fit()`:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let x = interner.intern(42);
+/// let y = interner.intern(1337);
+/// let z = y.clone();
+///
+/// drop(x);
+/// drop(y);
+///
+/// interner.shrink_to_fit();
+/// assert_eq!(interner.try_intern(&42), None);
+/// assert_eq!(interner.try_intern(&1337), Some(Arc::new(1337)));
+/// ```
+///
+/// `intern()`:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let x = interner.intern(42);
+/// let y = interner.intern(1337);
+///
+/// assert_eq!(*x, 42);
+/// assert_ne!(x, y);
+/// assert!(Arc::ptr_eq(&x, &interner.intern(42)));
+/// ```
+///
+/// `intern_cloned()`:
+/// ```rust
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let x = 42;
+/// let y = interner.intern_cloned(&x);
+///
+/// assert_eq!(x, *y);
+/// ```
+///
+/// `intern_slice()`:
+/// ```rust
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let x = interner.intern_slice(&[1, 2, 3]);
+///
+/// assert_eq!(x.as_ref(), &[1, 2, 3]);
+/// ```
+///
+/// `intern_vec()`:
+/// ```rust
+///
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let v = vec![1, 2, 3];
+/// let x = interner.intern_vec(v);
+///
+/// assert_eq!(x.as_ref(), &[1, 2, 3]);
+/// ```
+///
+/// `intern_str()`:
+/// ```rust
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let x = interner.intern_str("hello");
+///
+/// assert_eq!(x.as_ref(), "hello");
+/// ```
+///
+/// `intern_string()`:
+/// ```rust
+///
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let s = String::from("hello");
+/// let x = interner.intern_string(s);
+///
+/// assert_eq!(x.as_ref(), "hello");
+/// ```
+///
+/// `intern_any()`:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use refcount_interner::ArcInterner;
+/// let mut strings = ArcInterner::<str>::new();
+/// let x = strings.intern_any("x");
+/// let y = strings.intern_any(String::from("x"));
+/// assert!(Arc::ptr_eq(&x, &y));
+///
+/// let mut slices = ArcInterner::<[i32]>::new();
+/// let x = slices.intern_any(vec![1, 2]);
+/// let y = slices.intern_any(&[1, 2][..]);
+/// assert!(Arc::ptr_eq(&x, &y));
+///
+/// let mut numbers = ArcInterner::<i32>::new();
+/// let x = numbers.intern_any(42);
+/// assert!(Arc::ptr_eq(&x, &numbers.intern(42)));
+/// ```
+///
+/// `intern_opaque()`:
+/// ```rust
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::<str>::new();
+///
+/// let x = interner.intern_opaque("hello");
+/// let y = interner.intern_opaque(String::from("hello"));
+///
+/// assert_eq!(x, y);
+/// assert_eq!(x.len(), 5);
+/// ```
+///
+/// `on_low_hit_rate()`, which requires the function to be `Send` and `Sync`:
+/// ```rust
+/// # use std::sync::{Arc, Mutex};
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::new();
+///
+/// let rates = Arc::new(Mutex::new(Vec::new()));
+/// let r = rates.clone();
+/// interner.on_low_hit_rate(4, 0.5, move |rate| {
+///     r.lock().unwrap().push(rate)
+/// });
+///
+/// for _ in 0..8 {
+///     interner.intern(42);
+/// }
+/// assert!(rates.lock().unwrap().is_empty());
+///
+/// for i in 0..4 {
+///     interner.intern(i);
+/// }
+/// assert_eq!(*rates.lock().unwrap(), vec![0.25, 0.0]);
+/// ```
+///
+/// A function that is not `Send` cannot be registered:
+/// ```compile_fail
+/// # use std::rc::Rc;
+/// # use refcount_interner::ArcInterner;
+/// let mut interner = ArcInterner::<i32>::new();
+/// let local = Rc::new(());
+/// interner.on_low_hit_rate(4, 0.5, move |_| drop(local.clone()));
+/// ```
+pub type ArcInterner<T, B = HashSet<Arc<T>>> = RefCountInterner<Arc<T>, T, B>;
 
-    /// Attempt to get a reference to an already interned object.
-    ///
-    /// If the object has already been interned, an option containing a
-    /// reference to the already interned object will be returned.
-    ///
-    /// If the object has not yet been interned, `None` will be returned.
-    ///
-    /// # Example
-    /// ```rust
-    /// # use std::sync::Arc;
-    /// # use refcount_interner::ArcInterner;
-    /// let mut interner = ArcInterner::new();
-    ///
-    /// let x = interner.intern(42);
-    /// assert_eq!(interner.try_intern(&42), Some(Arc::new(42)));
-    /// assert_eq!(interner.try_intern(&1337), None);
-    /// ```
-    pub fn try_intern(&self, t: &T) -> Option<Arc<T>> {
-        self.0.get(t).cloned()
-    }
+/// A read-only interner produced by `ArcInterner::freeze()`
+///
+/// A frozen interner only supports looking up already interned objects by
+/// reference, so no lookup allocates or modifies reference counts. Use
+/// `thaw()` to convert it back into an `ArcInterner`.
+///
+/// Since a frozen interner cannot be modified, it can be shared between
+/// threads by reference without any locking.
+///
+/// # Example
+/// ```rust
+/// # use std::sync::Arc;
+/// use refcount_interner::ArcInterner;
+///
+/// let mut interner = ArcInterner::new();
+/// let x = interner.intern(42);
+///
+/// let frozen = interner.freeze();
+/// assert!(Arc::ptr_eq(&x, frozen.get(&42).unwrap()));
+/// assert_eq!(frozen.get(&1337), None);
+///
+/// std::thread::scope(|s| {
+///     s.spawn(|| assert_eq!(frozen.get(&42), Some(&x)));
+///     s.spawn(|| assert_eq!(frozen.get(&1337), None));
+/// });
+/// ```
+pub type FrozenArcInterner<T, B = HashSet<Arc<T>>> =
+    FrozenRefCountInterner<Arc<T>, T, B>;
 
-    /// Intern a boxed object
+impl<T: ?Sized + Hash + Eq, B: SetBackend<Arc<T>>> ArcInterner<T, B> {
+    /// Intern an already allocated `Arc<T>`
     ///
-    /// This method must be used to intern unsized types, since unsized types
-    /// cannot be passed to `intern()`. The two most common unsized types,
-    /// `&[T]` and `&str` can be interned with `intern_slice()` and
-    /// `intern_str()` as well.
-    ///
-    /// If the object has already been interned, the passed object will be
-    /// dropped and deallocated, and a reference to the already interned object
-    /// will be returned.
-    ///
-    /// If the object has not yet been interned, the passed object will be moved
-    /// into an `Arc<T>`, remembered for future calls to `intern()`, and
-    /// returned.
-    ///
-    /// # Example
-    /// ```rust
-    /// # use refcount_interner::ArcInterner;
-    /// let mut interner = ArcInterner::new();
-    ///
-    /// let x = Box::new(42);
-    /// let y = interner.intern_boxed(x);
-    ///
-    /// assert_eq!(*y, 42);
-    /// ```
-    pub fn intern_boxed(&mut self, t: Box<T>) -> Arc<T> {
-        if let Some(value) = self.0.get(t.as_ref()) {
-            value.clone()
-        } else {
-            let value: Arc<T> = Arc::from(t);
-            self.0.insert(value.clone());
-            value
-        }
-    }
-
-    /// Deallocate all interned objects that are no longer referenced and shrink
-    /// the internal storage to fit.
+    /// See `intern_shared()`.
     ///
     /// # Example
     /// ```rust
     /// # use std::sync::Arc;
-    /// # use refcount_interner::ArcInterner;
-    /// let mut interner = ArcInterner::new();
-    ///
-    /// let x = interner.intern(42);
-    /// let y = interner.intern(1337);
-    /// let z = y.clone();
-    ///
-    /// drop(x);
-    /// drop(y);
-    ///
-    /// interner.shrink_to_fit();
-    /// assert_eq!(interner.try_intern(&42), None);
-    /// assert_eq!(interner.try_intern(&1337), Some(Arc::new(1337)));
+    /// # use refcount_interner::{ArcInterner, CanonRule};
+    /// let a = Arc::new(42);
+    /// let b = Arc::new(42);
+    /// let lowest = if Arc::as_ptr(&a) < Arc::as_ptr(&b) { &a } else { &b };
+    ///
+    /// let mut forward = ArcInterner::new();
+    /// forward.set_canonicalization(CanonRule::LowestAddress);
+    /// forward.intern_arc(a.clone());
+    /// forward.intern_arc(b.clone());
+    ///
+    /// let mut backward = ArcInterner::new();
+    /// backward.set_canonicalization(CanonRule::LowestAddress);
+    /// backward.intern_arc(b.clone());
+    /// backward.intern_arc(a.clone());
+    ///
+    /// assert!(Arc::ptr_eq(&forward.intern(42), lowest));
+    /// assert!(Arc::ptr_eq(&backward.intern(42), lowest));
+    ///
+    /// let mut first_seen = ArcInterner::new();
+    /// first_seen.intern_arc(b.clone());
+    /// assert!(Arc::ptr_eq(&first_seen.intern_arc(a.clone()), &b));
     /// ```
-    pub fn shrink_to_fit(&mut self) {
-        self.0.retain(|value| Arc::strong_count(value) > 1);
-        self.0.shrink_to_fit();
+    pub fn intern_arc(&mut self, value: Arc<T>) -> Arc<T> {
+        self.intern_shared(value)
     }
 }
 
-impl<T: Sized + Hash + Eq> ArcInterner<T> {
-    /// Intern an owned object
+impl<T: Sized + Hash + Eq, B: SetBackend<Arc<T>>> ArcInterner<T, B> {
+    /// Intern an owned object, returning a pinned handle
     ///
-    /// If the object has already been interned, the passed object will be
-    /// dropped, and a reference to the already interned object will be
-    /// returned.
+    /// This behaves like `intern()`, but wraps the returned handle in a `Pin`
+    /// for code that requires pinned data, such as futures holding interned
+    /// objects across await points. Pinned handles to equal objects share the
+    /// same allocation.
     ///
-    /// If the object has not yet been interned, the passed object will be moved
-    /// into an `Arc<T>`, remembered for future calls to `intern()`, and
-    /// returned.
+    /// Since the interner also hands out unpinned handles to the same objects,
+    /// which could be used to move an object out of its allocation once all
+    /// other handles are dropped, only `Unpin` objects can be pinned.
     ///
     /// # Example
     /// ```rust
     /// # use std::sync::Arc;
+    /// # use std::pin::Pin;
     /// # use refcount_interner::ArcInterner;
+    /// fn assert_send_sync<T: Send + Sync>(_: &T) {}
+    ///
     /// let mut interner = ArcInterner::new();
     ///
-    /// let x = interner.intern(42);
-    /// let y = interner.intern(1337);
+    /// let x: Pin<Arc<u32>> = interner.intern_pinned(42);
+    /// let y = interner.intern_pinned(42);
     ///
     /// assert_eq!(*x, 42);
-    /// assert_ne!(x, y);
-    /// assert!(Arc::ptr_eq(&x, &interner.intern(42)));
+    /// assert!(std::ptr::eq(&*x, &*y));
+    /// assert_send_sync(&x);
     /// ```
-    pub fn intern(&mut self, t: T) -> Arc<T> {
-        if let Some(value) = self.0.get(&t) {
-            value.clone()
-        } else {
-            let value = Arc::new(t);
-            self.0.insert(value.clone());
-            value
-        }
+    pub fn intern_pinned(&mut self, t: T) -> Pin<Arc<T>>
+    where
+        T: Unpin,
+    {
+        Pin::new(self.intern(t))
     }
 }
 
-impl<T: Sized + Hash + Eq + Clone> ArcInterner<T> {
-    /// Intern a borrowed object, cloning if it has not yet been interned
+impl<T, B> ArcInterner<[T], B>
+    where T: Sized + Hash + Eq + Clone, B: SetBackend<Arc<[T]>>
+{
+    /// Intern a slice object, returning a pinned handle
     ///
-    /// If the object has already been interned, a reference to the already
-    /// interned object will be returned.
-    ///
-    /// If the object has not yet been interned, the passed object will be moved
-    /// into an `Arc<T>`, remembered for future calls to `intern()`, and
-    /// returned.
+    /// This behaves like `intern_slice()`, but wraps the returned handle in a
+    /// `Pin`. See `intern_pinned()` for why only `Unpin` slices can be pinned.
     ///
     /// # Example
     /// ```rust
     /// # use refcount_interner::ArcInterner;
+    /// fn assert_send_sync<T: Send + Sync>(_: &T) {}
+    ///
     /// let mut interner = ArcInterner::new();
     ///
-    /// let x = 42;
-    /// let y = interner.intern_cloned(&x);
+    /// let x = interner.intern_slice_pinned(&[1, 2, 3]);
+    /// let y = interner.intern_slice_pinned(&[1, 2, 3]);
     ///
-    /// assert_eq!(x, *y);
+    /// assert_eq!(&*x, &[1, 2, 3]);
+    /// assert!(std::ptr::eq(&*x, &*y));
+    /// assert_send_sync(&x);
     /// ```
-    pub fn intern_cloned(&mut self, t: &T) -> Arc<T> {
-        if let Some(value) = self.0.get(t) {
-            value.clone()
-        } else {
-            let value = Arc::new(t.clone());
-            self.0.insert(value.clone());
-            value
-        }
+    pub fn intern_slice_pinned(&mut self, t: &[T]) -> Pin<Arc<[T]>>
+    where
+        T: Unpin,
+    {
+        Pin::new(self.intern_slice(t))
     }
 }
 
-impl<T: Sized + Hash + Eq + Clone> ArcInterner<[T]> {
-    /// Intern a slice object
-    ///
-    /// This method can be used to intern slices without boxing them.
+impl<B: SetBackend<Arc<str>>> ArcInterner<str, B> {
+    /// Intern a string slice, returning a pinned handle
     ///
-    /// If the slice has already been interned, a reference to the already
-    /// interned slice will be returned.
-    ///
-    /// If the slice has not yet been interned, the passed object will be
-    /// cloned into an `Arc<[T]>`, remembered for future calls to `intern()`,
-    /// and returned.
+    /// This behaves like `intern_str()`, but wraps the returned handle in a
+    /// `Pin`.
     ///
     /// # Example
     /// ```rust
     /// # use refcount_interner::ArcInterner;
-    /// let mut interner = ArcInterner::new();
+    /// fn assert_send_sync<T: Send + Sync>(_: &T) {}
     ///
-    /// let x = interner.intern_slice(&[1, 2, 3]);
-    ///
-    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
-    /// ```
-    pub fn intern_slice(&mut self, t: &[T]) -> Arc<[T]> {
-        if let Some(value) = self.0.get(t) {
-            value.clone()
-        } else {
-            let value: Arc<[T]> = Arc::from(t);
-            self.0.insert(value.clone());
-            value
-        }
-    }
-
-    /// Intern an owned vector
-    ///
-    /// If the slice behind the vector has already been interned, a reference
-    /// to the already / interned slice will be returned.
-    ///
-    /// If the slice has not yet been interned, the passed vector will be moved
-    /// into an `Arc<[T]>`, remembered for future calls to `intern()`, and
-    /// returned.
-    ///
-    /// # Example
-    /// ```rust
-    ///
-    /// # use refcount_interner::ArcInterner;
     /// let mut interner = ArcInterner::new();
     ///
-    /// let v = vec![1, 2, 3];
-    /// let x = interner.intern_vec(v);
+    /// let x = interner.intern_str_pinned("hello");
+    /// let y = interner.intern_str_pinned("hello");
     ///
-    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
+    /// assert_eq!(&*x, "hello");
+    /// assert!(std::ptr::eq(&*x, &*y));
+    /// assert_send_sync(&x);
     /// ```
-    pub fn intern_vec(&mut self, t: Vec<T>) -> Arc<[T]> {
-        self.intern_boxed(t.into_boxed_slice())
+    pub fn intern_str_pinned(&mut self, t: &str) -> Pin<Arc<str>> {
+        Pin::new(self.intern_str(t))
     }
 }
 
-impl ArcInterner<str> {
-    /// Intern a string slice
-    ///
-    /// This method can be used to intern string slices without boxing them.
-    ///
-    /// If the string slice has already been interned, a reference to the
-    /// already interned string slice will be returned.
+impl<T, B> ArcInterner<Arc<T>, B>
+    where T: ?Sized + Hash + Eq, B: SetBackend<Arc<Arc<T>>>
+{
+    /// Intern a reference-counted pointer
+    ///
+    /// Since `Arc<T>` is hashed and compared by value, two pointers to equal
+    /// objects are interned as the same `Arc<Arc<T>>`, even if they point to
+    /// different allocations. This can be used to deduplicate already interned
+    /// objects a second time, so that collections of them can be compared by
+    /// pointer.
+    ///
+    /// If an equal pointer has already been interned, the passed pointer will
+    /// be dropped, and a reference to the already interned pointer will be
+    /// returned.
     ///
-    /// If the string slice has not yet been interned, the passed object will be
-    /// cloned into an `Arc<str>`, remembered for future calls to `intern()`,
+    /// If no equal pointer has been interned yet, the passed pointer will be
+    /// moved into an `Arc<Arc<T>>`, remembered for future calls to `intern()`,
     /// and returned.
     ///
     /// # Example
     /// ```rust
+    /// # use std::sync::Arc;
     /// # use refcount_interner::ArcInterner;
     /// let mut interner = ArcInterner::new();
     ///
-    /// let x = interner.intern_str("hello");
+    /// let x: Arc<str> = Arc::from("hello");
+    /// let y: Arc<str> = Arc::from("hello");
     ///
-    /// assert_eq!(x.as_ref(), "hello");
+    /// let a = interner.intern_handle(x);
+    /// let b = interner.intern_handle(y);
+    ///
+    /// assert!(Arc::ptr_eq(&a, &b));
     /// ```
-    pub fn intern_str(&mut self, t: &str) -> Arc<str> {
-        if let Some(value) = self.0.get(t) {
-            value.clone()
-        } else {
-            let value: Arc<str> = Arc::from(t);
-            self.0.insert(value.clone());
-            value
-        }
+    pub fn intern_handle(&mut self, h: Arc<T>) -> Arc<Arc<T>> {
+        self.intern(h)
     }
+}
 
-    /// Intern an owned string
-    ///
-    /// If the string has already been interned, a reference to the already
-    /// interned string slice will be returned.
-    ///
-    /// If the string has not yet been interned, the passed string will be moved
-    /// into an `Arc<str>`, remembered for future calls to `intern()`, and
-    /// returned.
-    ///
-    /// # Example
-    /// ```rust
-    ///
-    /// # use refcount_interner::ArcInterner;
-    /// let mut interner = ArcInterner::new();
-    ///
-    /// let s = String::from("hello");
-    /// let x = interner.intern_string(s);
-    ///
-    /// assert_eq!(x.as_ref(), "hello");
-    /// ```
-    pub fn intern_string(&mut self, t: String) -> Arc<str> {
-        self.intern_boxed(t.into_boxed_str())
+/// Collect already allocated handles into a new interner
+///
+/// Each handle is interned with `intern_arc()`.
+///
+/// # Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use refcount_interner::ArcInterner;
+/// let x = Arc::new(42);
+/// let interner: ArcInterner<i32> = vec![x.clone()].into_iter().collect();
+///
+/// assert!(Arc::ptr_eq(&interner.try_intern(&42).unwrap(), &x));
+/// ```
+impl<T, B> FromIterator<Arc<T>> for ArcInterner<T, B>
+    where T: ?Sized + Hash + Eq, B: SetBackend<Arc<T>> + Default
+{
+    fn from_iter<I: IntoIterator<Item = Arc<T>>>(iter: I) -> ArcInterner<T, B> {
+        let mut interner = ArcInterner::default();
+        for value in iter {
+            interner.intern_arc(value);
+        }
+        interner
     }
 }
@@ -0,0 +1,218 @@
+use std::ops::Deref;
+use std::borrow::Borrow;
+use std::hash::{Hash, BuildHasher};
+use std::collections::{HashSet, hash_set};
+
+/// A set of handles used as the storage of `RcInterner` and `ArcInterner`
+///
+/// `H` is the handle type stored in the set, `Rc<T>` or `Arc<T>`. Lookups and
+/// removals are done by the value the handle points to. The interners use
+/// `HashSet` by default, but can be created with any backend using
+/// `with_backend()`, e.g. a set specialized for a fixed vocabulary.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::{RcInterner, SetBackend};
+///
+/// struct VecSet<T: ?Sized>(Vec<Rc<T>>);
+///
+/// impl<T: ?Sized + Eq> SetBackend<Rc<T>> for VecSet<T> {
+///     type Iter<'a> = std::slice::Iter<'a, Rc<T>> where T: 'a;
+///
+///     fn get(&self, value: &T) -> Option<&Rc<T>> {
+///         self.0.iter().find(|h| h.as_ref() == value)
+///     }
+///
+///     fn insert(&mut self, value: Rc<T>) -> bool {
+///         let new = self.get(&value).is_none();
+///         if new {
+///             self.0.push(value);
+///         }
+///         new
+///     }
+///
+///     fn remove(&mut self, value: &T) -> bool {
+///         let len = self.0.len();
+///         self.0.retain(|h| h.as_ref() != value);
+///         self.0.len() != len
+///     }
+///
+///     fn retain<F: FnMut(&Rc<T>) -> bool>(&mut self, f: F) {
+///         self.0.retain(f)
+///     }
+///
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+///
+///     fn capacity(&self) -> usize {
+///         self.0.capacity()
+///     }
+///
+///     fn iter(&self) -> Self::Iter<'_> {
+///         self.0.iter()
+///     }
+/// }
+///
+/// let mut interner = RcInterner::with_backend(VecSet(Vec::new()));
+///
+/// let x = interner.intern_str("hello");
+/// let y = interner.intern_str("world");
+/// assert!(Rc::ptr_eq(&x, &interner.intern_str("hello")));
+/// assert!(!Rc::ptr_eq(&x, &y));
+///
+/// drop(x);
+/// interner.shrink_to_fit();
+/// assert_eq!(interner.try_intern("hello"), None);
+/// assert_eq!(interner.try_intern("world"), Some(y));
+/// ```
+pub trait SetBackend<H: Deref> {
+    /// The iterator returned by `iter()`.
+    type Iter<'a>: Iterator<Item = &'a H> where Self: 'a, H: 'a;
+
+    /// Get the handle pointing to a value equal to `value`.
+    fn get(&self, value: &H::Target) -> Option<&H>;
+
+    /// Insert a handle, returning whether no handle to an equal value was
+    /// present.
+    fn insert(&mut self, value: H) -> bool;
+
+    /// Remove the handle pointing to a value equal to `value`, returning
+    /// whether it was present.
+    fn remove(&mut self, value: &H::Target) -> bool;
+
+    /// Keep only the handles for which `f` returns `true`.
+    fn retain<F: FnMut(&H) -> bool>(&mut self, f: F);
+
+    /// Get the number of handles in the set.
+    fn len(&self) -> usize;
+
+    /// Check whether the set is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of handles the set can hold without growing.
+    fn capacity(&self) -> usize;
+
+    /// Get the capacity of a set created with room for at least `n` handles.
+    ///
+    /// The default implementation returns `n`.
+    fn capacity_for(n: usize) -> usize where Self: Sized {
+        n
+    }
+
+    /// Iterate over the handles in the set.
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Insert a handle, replacing the handle to an equal value if present.
+    fn replace(&mut self, value: H) {
+        self.remove(&value);
+        self.insert(value);
+    }
+
+    /// Reserve space for at least `additional` more handles.
+    ///
+    /// The default implementation does nothing.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Shrink the storage of the set to fit its handles.
+    ///
+    /// The default implementation does nothing.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Shrink the storage of the set to fit its handles, but keep room for at
+    /// least `min_capacity` handles.
+    ///
+    /// The default implementation does nothing.
+    fn shrink_to(&mut self, min_capacity: usize) {
+        let _ = min_capacity;
+    }
+
+    /// Remove all handles.
+    fn clear(&mut self) {
+        self.retain(|_| false);
+    }
+}
+
+impl<H, S> SetBackend<H> for HashSet<H, S>
+    where
+        H: Deref + Borrow<H::Target> + Hash + Eq,
+        H::Target: Hash + Eq,
+        S: BuildHasher,
+{
+    type Iter<'a> = hash_set::Iter<'a, H> where Self: 'a;
+
+    fn get(&self, value: &H::Target) -> Option<&H> {
+        HashSet::get(self, value)
+    }
+
+    fn insert(&mut self, value: H) -> bool {
+        HashSet::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &H::Target) -> bool {
+        HashSet::remove(self, value)
+    }
+
+    fn retain<F: FnMut(&H) -> bool>(&mut self, f: F) {
+        HashSet::retain(self, f)
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        HashSet::capacity(self)
+    }
+
+    fn capacity_for(n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+
+        // the table has a power of two number of buckets, of which 7/8 can be
+        // used, or all but one for tables with at most 8 buckets
+        let buckets = if n < 4 {
+            4
+        } else if n < 8 {
+            8
+        } else {
+            (n.saturating_mul(8) / 7).next_power_of_two()
+        };
+
+        if buckets <= 8 {
+            buckets - 1
+        } else {
+            buckets / 8 * 7
+        }
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        HashSet::iter(self)
+    }
+
+    fn replace(&mut self, value: H) {
+        HashSet::replace(self, value);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        HashSet::reserve(self, additional)
+    }
+
+    fn shrink_to_fit(&mut self) {
+        HashSet::shrink_to_fit(self)
+    }
+
+    fn shrink_to(&mut self, min_capacity: usize) {
+        HashSet::shrink_to(self, min_capacity)
+    }
+
+    fn clear(&mut self) {
+        HashSet::clear(self)
+    }
+}
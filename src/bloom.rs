@@ -0,0 +1,116 @@
+use std::hash::{Hash, BuildHasher};
+use std::collections::hash_map::RandomState;
+
+/// A bloom filter over hashable values
+#[derive(Debug, Clone)]
+pub(crate) struct BloomFilter {
+    bits: Vec<u64>,
+    items: usize,
+    probed: Option<u64>,
+    skipped: u64,
+    hasher: RandomState,
+}
+
+const HASHES: u64 = 3;
+
+/// Number of bits per inserted value below which the filter is saturated
+const BITS_PER_ITEM: usize = 8;
+
+fn bit_indices(hash: u64, words: usize) -> impl Iterator<Item = usize> {
+    let len = words as u64 * 64;
+    let h1 = hash & 0xffff_ffff;
+    let h2 = (hash >> 32) | 1;
+
+    (0..HASHES).map(move |i| {
+        (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize
+    })
+}
+
+impl BloomFilter {
+    pub(crate) fn new(bits: usize) -> BloomFilter {
+        BloomFilter {
+            bits: vec![0; bits.div_ceil(64).max(1)],
+            items: 0,
+            probed: None,
+            skipped: 0,
+            hasher: RandomState::new(),
+        }
+    }
+
+    pub(crate) fn insert<T: ?Sized + Hash>(&mut self, t: &T) {
+        let hash = self.hasher.hash_one(t);
+        self.insert_hash(hash);
+    }
+
+    /// Insert the value passed to the last call to `may_contain()`
+    ///
+    /// This reuses the hash computed by `may_contain()`, so `t` must be equal
+    /// to the value passed to it.
+    pub(crate) fn insert_probed<T: ?Sized + Hash>(&mut self, t: &T) {
+        let hash = match self.probed.take() {
+            Some(hash) => hash,
+            None => self.hasher.hash_one(t),
+        };
+        self.insert_hash(hash);
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        for i in bit_indices(hash, self.bits.len()) {
+            self.bits[i / 64] |= 1 << (i % 64);
+        }
+        self.items += 1;
+    }
+
+    /// Returns `false` if the value has definitely not been inserted, and
+    /// counts this as a skipped lookup
+    ///
+    /// The hash of the value is kept for the next call to `insert_probed()`.
+    pub(crate) fn may_contain<T: ?Sized + Hash>(&mut self, t: &T) -> bool {
+        let hash = self.hasher.hash_one(t);
+        self.probed = Some(hash);
+
+        let found = self.contains_hash(hash);
+        if !found {
+            self.skipped += 1;
+        }
+
+        found
+    }
+
+    /// Returns `false` if the value has definitely not been inserted
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub(crate) fn contains<T: ?Sized + Hash>(&self, t: &T) -> bool {
+        self.contains_hash(self.hasher.hash_one(t))
+    }
+
+    fn contains_hash(&self, hash: u64) -> bool {
+        bit_indices(hash, self.bits.len())
+            .all(|i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    /// Returns `true` if so many values have been inserted that most lookups
+    /// would be false positives
+    pub(crate) fn is_saturated(&self) -> bool {
+        self.items * BITS_PER_ITEM > self.bits.len() * 64
+    }
+
+    /// Remove all values, growing the filter so that it can hold twice as
+    /// many as `items` values before it is saturated
+    pub(crate) fn clear(&mut self, items: usize) {
+        let words = (2 * items * BITS_PER_ITEM).div_ceil(64);
+        if words > self.bits.len() {
+            self.bits = vec![0; words];
+        } else {
+            for word in &mut self.bits {
+                *word = 0;
+            }
+        }
+
+        self.items = 0;
+        self.probed = None;
+    }
+
+    pub(crate) fn skipped(&self) -> u64 {
+        self.skipped
+    }
+}
@@ -0,0 +1,128 @@
+use std::rc::Rc;
+use std::time::Duration;
+use std::convert::TryFrom;
+
+use crate::RcInterner;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// An interner rounding values down to buckets of a fixed granularity before
+/// interning them
+///
+/// Values that fall into the same bucket share one interned handle to the
+/// start of the bucket. Currently, `Duration` values are supported.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// # use std::time::Duration;
+/// use refcount_interner::BucketedInterner;
+///
+/// let minute = Duration::from_secs(60);
+/// let mut interner = BucketedInterner::with_granularity(minute);
+///
+/// let x = interner.intern(Duration::from_secs(125));
+/// let y = interner.intern(Duration::from_secs(179));
+/// let z = interner.intern(Duration::from_secs(180));
+///
+/// assert_eq!(*x, Duration::from_secs(120));
+/// assert!(Rc::ptr_eq(&x, &y));
+/// assert!(!Rc::ptr_eq(&y, &z));
+/// ```
+#[derive(Debug)]
+pub struct BucketedInterner<T> {
+    interner: RcInterner<T>,
+    granularity: T,
+}
+
+impl BucketedInterner<Duration> {
+    /// Create a new, empty interner rounding durations down to multiples of
+    /// `granularity`.
+    ///
+    /// # Panics
+    /// Panics if `granularity` is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use refcount_interner::BucketedInterner;
+    /// let granularity = Duration::from_millis(100);
+    /// let mut interner = BucketedInterner::with_granularity(granularity);
+    /// # let x = interner.intern(Duration::from_millis(42));
+    /// ```
+    pub fn with_granularity(granularity: Duration)
+        -> BucketedInterner<Duration>
+    {
+        assert!(granularity > Duration::from_secs(0), "granularity is zero");
+
+        BucketedInterner {
+            interner: RcInterner::new(),
+            granularity,
+        }
+    }
+
+    /// Get the granularity of the buckets.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use refcount_interner::BucketedInterner;
+    /// let granularity = Duration::from_millis(100);
+    /// let interner = BucketedInterner::with_granularity(granularity);
+    /// assert_eq!(interner.granularity(), granularity);
+    /// ```
+    pub fn granularity(&self) -> Duration {
+        self.granularity
+    }
+
+    /// Intern a duration, rounded down to a multiple of the granularity
+    ///
+    /// If the bucket of the duration has already been interned, a reference
+    /// to the already interned bucket will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::time::Duration;
+    /// # use refcount_interner::BucketedInterner;
+    /// let granularity = Duration::from_millis(100);
+    /// let mut interner = BucketedInterner::with_granularity(granularity);
+    ///
+    /// let x = interner.intern(Duration::from_millis(1210));
+    /// let y = interner.intern(Duration::from_millis(1299));
+    ///
+    /// assert_eq!(*x, Duration::from_millis(1200));
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern(&mut self, d: Duration) -> Rc<Duration> {
+        let nanos = d.as_nanos();
+        let bucket = nanos - nanos % self.granularity.as_nanos();
+
+        let secs = u64::try_from(bucket / NANOS_PER_SEC)
+            .expect("bucket of a duration is a duration");
+        let bucket = Duration::new(secs, (bucket % NANOS_PER_SEC) as u32);
+        self.interner.intern(bucket)
+    }
+
+    /// Deallocate all interned buckets that are no longer referenced and
+    /// shrink the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::time::Duration;
+    /// # use refcount_interner::BucketedInterner;
+    /// let second = Duration::from_secs(1);
+    /// let mut interner = BucketedInterner::with_granularity(second);
+    ///
+    /// let x = interner.intern(Duration::from_millis(1500));
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// let y = interner.intern(Duration::from_millis(1500));
+    /// assert_eq!(Rc::strong_count(&y), 2);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.interner.shrink_to_fit();
+    }
+}
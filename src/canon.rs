@@ -0,0 +1,13 @@
+/// The rule deciding which allocation becomes canonical when an already
+/// allocated handle is interned
+///
+/// See `RcInterner::intern_rc()` and `ArcInterner::intern_arc()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CanonRule {
+    /// The first allocation interned for a value stays canonical.
+    #[default]
+    FirstSeen,
+    /// The allocation with the lowest address becomes canonical, regardless
+    /// of the order handles are interned in.
+    LowestAddress,
+}
@@ -0,0 +1,26 @@
+/// A point in the history of an interner that it can be rolled back to
+///
+/// Checkpoints are created with `checkpoint()` on `RcInterner` or
+/// `ArcInterner`, and consumed by `rollback()` or `commit()`.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+///
+/// let x = interner.intern(42);
+/// let checkpoint = interner.checkpoint();
+/// let y = interner.intern(1337);
+///
+/// interner.rollback(checkpoint);
+/// assert_eq!(interner.try_intern(&42), Some(x));
+/// assert_eq!(interner.try_intern(&1337), None);
+/// ```
+#[derive(Debug)]
+#[must_use = "a checkpoint keeps the interner recording until committed"]
+pub struct Checkpoint {
+    pub(crate) position: usize,
+    #[cfg(debug_assertions)]
+    pub(crate) interner: u64,
+}
@@ -0,0 +1,300 @@
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+use std::collections::hash_map::RandomState;
+
+use crate::Symbol;
+
+const EMPTY: u32 = u32::MAX;
+
+/// A symbol interner storing all interned strings in a single buffer
+///
+/// Interned strings are appended to one contiguous `String`, and each symbol
+/// maps to a range of that buffer. Symbols are looked up by string with an
+/// open-addressing hash table of symbol ids. Interning a string therefore does
+/// not allocate per string; the buffer, the ranges and the hash table only
+/// grow geometrically, or not at all if enough capacity was reserved with
+/// `with_capacity()`.
+///
+/// Interned strings are never deallocated.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::CompactSymbolInterner;
+///
+/// let mut interner = CompactSymbolInterner::new();
+///
+/// let x = interner.intern_symbol("hello");
+/// let y = interner.intern_symbol("world");
+///
+/// assert_eq!(x, interner.intern_symbol("hello"));
+/// assert_ne!(x, y);
+/// assert_eq!(interner.resolve(x), Some("hello"));
+/// ```
+#[derive(Debug, Default)]
+pub struct CompactSymbolInterner {
+    arena: String,
+    ranges: Vec<(u32, u32)>,
+    table: Vec<u32>,
+    hasher: RandomState,
+}
+
+impl CompactSymbolInterner {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let mut interner = CompactSymbolInterner::new();
+    /// # let x = interner.intern_symbol("hello");
+    /// ```
+    pub fn new() -> CompactSymbolInterner {
+        Default::default()
+    }
+
+    /// Create a new, empty interner with space for at least `symbols`
+    /// symbols with a total length of at least `bytes` bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::alloc::{GlobalAlloc, Layout, System};
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # struct Counting;
+    /// # static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+    /// # unsafe impl GlobalAlloc for Counting {
+    /// #     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    /// #         ALLOCS.fetch_add(1, Ordering::SeqCst);
+    /// #         System.alloc(layout)
+    /// #     }
+    /// #     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    /// #         System.dealloc(ptr, layout)
+    /// #     }
+    /// #     unsafe fn realloc(&self, ptr: *mut u8, l: Layout, size: usize)
+    /// #         -> *mut u8
+    /// #     {
+    /// #         ALLOCS.fetch_add(1, Ordering::SeqCst);
+    /// #         System.realloc(ptr, l, size)
+    /// #     }
+    /// # }
+    /// # #[global_allocator]
+    /// # static GLOBAL: Counting = Counting;
+    /// # fn main() {
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let strings: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+    /// let bytes = strings.iter().map(String::len).sum();
+    ///
+    /// let mut interner = CompactSymbolInterner::with_capacity(1000, bytes);
+    ///
+    /// let before = ALLOCS.load(Ordering::SeqCst);
+    /// let symbols: Vec<_> = strings.iter()
+    ///     .map(|s| interner.intern_symbol(s))
+    ///     .collect();
+    /// assert_eq!(ALLOCS.load(Ordering::SeqCst), before + 1);
+    ///
+    /// assert_eq!(interner.len(), 1000);
+    /// for (s, &symbol) in strings.iter().zip(&symbols) {
+    ///     assert_eq!(interner.resolve(symbol), Some(s.as_str()));
+    ///     assert_eq!(interner.intern_symbol(s), symbol);
+    /// }
+    /// # }
+    /// ```
+    pub fn with_capacity(symbols: usize, bytes: usize)
+        -> CompactSymbolInterner
+    {
+        let mut interner = CompactSymbolInterner {
+            arena: String::with_capacity(bytes),
+            ranges: Vec::with_capacity(symbols),
+            table: Vec::new(),
+            hasher: RandomState::new(),
+        };
+        interner.rehash(table_size_for(symbols));
+        interner
+    }
+
+    /// Intern a string slice, returning its symbol
+    ///
+    /// If the string slice has already been interned, the symbol of the
+    /// already interned string slice will be returned.
+    ///
+    /// If the string slice has not yet been interned, it is appended to the
+    /// internal buffer and a new symbol is returned.
+    ///
+    /// # Panics
+    /// Panics if the total length of all interned strings or the number of
+    /// symbols would exceed `u32::MAX`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let mut interner = CompactSymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    /// assert_eq!(x, interner.intern_symbol("hello"));
+    /// ```
+    pub fn intern_symbol(&mut self, s: &str) -> Symbol {
+        if let Some(symbol) = self.get(s) {
+            return symbol;
+        }
+
+        if (self.ranges.len() + 1) * 2 > self.table.len() {
+            self.rehash(table_size_for(self.ranges.len() + 1));
+        }
+
+        let id = u32::try_from(self.ranges.len())
+            .ok()
+            .filter(|&id| id != EMPTY)
+            .expect("too many symbols");
+        let start = u32::try_from(self.arena.len())
+            .expect("interned strings too long");
+        let len = u32::try_from(s.len())
+            .ok()
+            .filter(|len| start.checked_add(*len).is_some())
+            .expect("interned strings too long");
+
+        self.arena.push_str(s);
+        self.ranges.push((start, len));
+
+        let slot = self.find_slot(s);
+        self.table[slot] = id;
+        Symbol::from_u32(id)
+    }
+
+    /// Attempt to get the symbol of an already interned string slice.
+    ///
+    /// If the string slice has not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let mut interner = CompactSymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    /// assert_eq!(interner.get("hello"), Some(x));
+    /// assert_eq!(interner.get("world"), None);
+    /// ```
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        if self.table.is_empty() {
+            return None;
+        }
+
+        match self.table[self.find_slot(s)] {
+            EMPTY => None,
+            id => Some(Symbol::from_u32(id)),
+        }
+    }
+
+    /// Get the string slice of a symbol
+    ///
+    /// If the symbol was not returned by this interner, `None` will be
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{CompactSymbolInterner, Symbol};
+    /// let mut interner = CompactSymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    /// assert_eq!(interner.resolve(x), Some("hello"));
+    /// assert_eq!(interner.resolve(Symbol::from_u32(42)), None);
+    /// ```
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        let &(start, len) = self.ranges.get(symbol.index())?;
+        Some(&self.arena[start as usize..(start + len) as usize])
+    }
+
+    /// Get the number of interned strings.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let mut interner = CompactSymbolInterner::new();
+    ///
+    /// interner.intern_symbol("hello");
+    /// interner.intern_symbol("hello");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Check whether no strings have been interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let interner = CompactSymbolInterner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let mut interner = CompactSymbolInterner::new();
+    ///
+    /// for i in 0..100 {
+    ///     interner.intern_symbol(&i.to_string());
+    ///     interner.check_invariants();
+    /// }
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        for &(start, len) in &self.ranges {
+            let end = start as usize + len as usize;
+            assert!(self.arena.is_char_boundary(start as usize), "bad range");
+            assert!(self.arena.is_char_boundary(end), "bad range");
+        }
+
+        for id in 0..self.ranges.len() as u32 {
+            let symbol = Symbol::from_u32(id);
+            let s = self.resolve(symbol).unwrap();
+            assert_eq!(self.get(s), Some(symbol), "symbol not found by lookup");
+        }
+
+        let used = self.table.iter().filter(|&&id| id != EMPTY).count();
+        assert_eq!(used, self.ranges.len(), "hash table out of sync");
+        assert!(used * 2 <= self.table.len(), "hash table too full");
+    }
+
+    /// Find the slot of a string in the hash table, or the empty slot where it
+    /// would be inserted
+    fn find_slot(&self, s: &str) -> usize {
+        let mask = self.table.len() - 1;
+        let mut slot = self.hasher.hash_one(s) as usize & mask;
+        loop {
+            match self.table[slot] {
+                EMPTY => return slot,
+                id if self.resolve(Symbol::from_u32(id)) == Some(s) => {
+                    return slot;
+                }
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    fn rehash(&mut self, size: usize) {
+        if size <= self.table.len() {
+            return;
+        }
+
+        self.table.clear();
+        self.table.resize(size, EMPTY);
+        for id in 0..self.ranges.len() as u32 {
+            let s = self.resolve(Symbol::from_u32(id)).unwrap();
+            let slot = self.find_slot(s);
+            self.table[slot] = id;
+        }
+    }
+}
+
+/// Get the hash table size for a number of symbols, keeping the load factor
+/// at or below one half
+fn table_size_for(symbols: usize) -> usize {
+    (symbols * 2).next_power_of_two().max(8)
+}
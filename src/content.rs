@@ -0,0 +1,338 @@
+use std::rc::Rc;
+use std::collections::HashMap;
+
+/// Round constants of SHA-256
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5,
+    0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+    0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc,
+    0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+    0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+    0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3,
+    0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5,
+    0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+    0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Initial hash value of SHA-256
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// An interner identifying interned objects by the SHA-256 digest of their
+/// contents
+///
+/// Each interned object is stored under the digest of its bytes, so equal
+/// contents always map to the same digest, and the canonical handle of an
+/// object can be looked up by its digest alone, e.g. after the digest has been
+/// persisted or received from another process. Two objects with the same
+/// digest are assumed to be equal.
+///
+/// Interned objects will be deallocated when there are no references to them
+/// any more and `shrink_to_fit()` is called on the interner.
+///
+/// This interner is only available with the `content-hash` feature.
+///
+/// # Example
+/// ```rust
+/// use std::rc::Rc;
+/// use refcount_interner::ContentInterner;
+///
+/// let mut interner = ContentInterner::new();
+///
+/// let x = interner.intern("abc");
+/// let digest = interner.digest("abc").unwrap();
+///
+/// assert_eq!(digest[..4], [0xba, 0x78, 0x16, 0xbf]);
+/// assert!(Rc::ptr_eq(&interner.get_by_digest(&digest).unwrap(), &x));
+/// ```
+#[derive(Debug)]
+pub struct ContentInterner<T: ?Sized> {
+    objects: HashMap<[u8; 32], Rc<T>>,
+}
+
+impl<T: ?Sized> Default for ContentInterner<T> {
+    fn default() -> ContentInterner<T> {
+        ContentInterner {
+            objects: HashMap::new(),
+        }
+    }
+}
+
+impl<T: ?Sized + AsRef<[u8]>> ContentInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::new();
+    /// # let x = interner.intern("hello");
+    /// ```
+    pub fn new() -> ContentInterner<T> {
+        Default::default()
+    }
+
+    /// Intern a borrowed object
+    ///
+    /// If an object with the same digest has already been interned, a
+    /// reference to the already interned object will be returned.
+    ///
+    /// If no object with the same digest has been interned yet, the passed
+    /// object will be cloned into an `Rc<T>`, remembered for future calls to
+    /// `intern()`, and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::new();
+    ///
+    /// let x = interner.intern(&b"hello"[..]);
+    /// let y = interner.intern(&b"hello"[..]);
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern(&mut self, t: &T) -> Rc<T>
+    where
+        for<'a> Rc<T>: From<&'a T>,
+    {
+        self.objects.entry(sha256(t.as_ref()))
+            .or_insert_with(|| Rc::from(t))
+            .clone()
+    }
+
+    /// Intern a boxed object
+    ///
+    /// If an object with the same digest has already been interned, the passed
+    /// object will be dropped and deallocated, and a reference to the already
+    /// interned object will be returned.
+    ///
+    /// If no object with the same digest has been interned yet, the passed
+    /// object will be moved into an `Rc<T>`, remembered for future calls to
+    /// `intern()`, and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::new();
+    ///
+    /// let x = interner.intern_boxed(String::from("hello").into_boxed_str());
+    /// let y = interner.intern("hello");
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
+        self.objects.entry(sha256(t.as_ref().as_ref()))
+            .or_insert_with(|| Rc::from(t))
+            .clone()
+    }
+
+    /// Get the digest of an already interned object.
+    ///
+    /// If the object has not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::new();
+    ///
+    /// let x = interner.intern("hello");
+    /// let y = interner.intern("world");
+    ///
+    /// assert_eq!(interner.digest("hello"), interner.digest("hello"));
+    /// assert_ne!(interner.digest("hello"), interner.digest("world"));
+    /// assert_eq!(interner.digest("abc"), None);
+    /// ```
+    ///
+    /// The digest is the SHA-256 digest of the bytes of the object:
+    ///
+    /// ```rust
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::<[u8]>::new();
+    /// let mut digest = |bytes: &[u8]| {
+    ///     interner.intern(bytes);
+    ///     interner.digest(bytes).unwrap().iter()
+    ///         .map(|byte| format!("{:02x}", byte))
+    ///         .collect::<String>()
+    /// };
+    ///
+    /// assert_eq!(digest(b""), "e3b0c44298fc1c149afbf4c8996fb924\
+    ///     27ae41e4649b934ca495991b7852b855");
+    /// assert_eq!(digest(b"abc"), "ba7816bf8f01cfea414140de5dae2223\
+    ///     b00361a396177a9cb410ff61f20015ad");
+    /// assert_eq!(
+    ///     digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+    ///     "248d6a61d20638b8e5c026930c3e6039\
+    ///     a33ce45964ff2167f6ecedd419db06c1");
+    /// assert_eq!(digest(&[b'a'; 55]), "9f4390f8d30c2dd92ec9f095b65e2b9a\
+    ///     e9b0a925a5258e241c9f1e910f734318");
+    /// assert_eq!(digest(&[b'a'; 56]), "b35439a4ac6f0948b6d6f9e3c6af0f5f\
+    ///     590ce20f1bde7090ef7970686ec6738a");
+    /// assert_eq!(digest(&[b'a'; 64]), "ffe054fe7ae0cb6dc65c3af9b61d5209\
+    ///     f439851db43d0ba5997337df154668eb");
+    /// assert_eq!(digest(&vec![b'a'; 1_000_000]), "cdc76e5c9914fb9281a1c7e2\
+    ///     84d73e67f1809a48a497200e046d39ccc7112cd0");
+    /// ```
+    pub fn digest(&self, t: &T) -> Option<[u8; 32]> {
+        let digest = sha256(t.as_ref());
+        if self.objects.contains_key(&digest) {
+            Some(digest)
+        } else {
+            None
+        }
+    }
+
+    /// Get the interned object with the given digest.
+    ///
+    /// If no object with this digest has been interned, `None` will be
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::new();
+    ///
+    /// let x = interner.intern("hello");
+    /// let digest = interner.digest("hello").unwrap();
+    ///
+    /// let y = interner.get_by_digest(&digest).unwrap();
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// assert_eq!(interner.get_by_digest(&[0; 32]), None);
+    /// ```
+    pub fn get_by_digest(&self, digest: &[u8; 32]) -> Option<Rc<T>> {
+        self.objects.get(digest).cloned()
+    }
+
+    /// Get the number of interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::new();
+    ///
+    /// interner.intern("hello");
+    /// interner.intern("hello");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Check whether no objects have been interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ContentInterner;
+    /// let interner = ContentInterner::<str>::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ContentInterner;
+    /// let mut interner = ContentInterner::new();
+    ///
+    /// let x = interner.intern("hello");
+    /// interner.intern("world");
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.len(), 1);
+    /// assert!(interner.digest("world").is_none());
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.objects.retain(|_, value| Rc::strong_count(value) > 1);
+        self.objects.shrink_to_fit();
+    }
+}
+
+/// Compute the SHA-256 digest of a byte slice
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let bits = (bytes.len() as u64).wrapping_mul(8);
+
+    let mut state = H0;
+    let mut blocks = bytes.chunks_exact(64);
+    for block in &mut blocks {
+        compress(&mut state, block);
+    }
+
+    // the remaining bytes, the 0x80 marker and the length take up one block,
+    // or two if fewer than 9 bytes are left in the first one
+    let rest = blocks.remainder();
+    let mut tail = [0; 128];
+    tail[..rest.len()].copy_from_slice(rest);
+    tail[rest.len()] = 0x80;
+    let end = if rest.len() < 56 { 64 } else { 128 };
+    tail[end - 8..end].copy_from_slice(&bits.to_be_bytes());
+    for block in tail[..end].chunks_exact(64) {
+        compress(&mut state, block);
+    }
+
+    let mut digest = [0; 32];
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(&state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Apply the SHA-256 compression function to a 64-byte block
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut w = [0; 64];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7)
+            ^ w[i - 15].rotate_right(18)
+            ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17)
+            ^ w[i - 2].rotate_right(19)
+            ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (word, value) in state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(*value);
+    }
+}
@@ -0,0 +1,167 @@
+use std::rc::Rc;
+use std::hash::{Hash, BuildHasher};
+use std::collections::hash_map::RandomState;
+
+use crate::RcInterner;
+
+const DEPTH: usize = 4;
+
+/// An interner that only interns objects that are seen frequently
+///
+/// The number of times each object has been passed to `intern()` is
+/// estimated with a count-min sketch of fixed size. Once the estimated count
+/// of an object exceeds the threshold, it is interned in an `RcInterner`, and
+/// all later calls to `intern()` return the interned object. Until then, every
+/// call to `intern()` returns a new, non-deduplicated `Rc<T>`.
+///
+/// The count-min sketch never underestimates counts, but may overestimate
+/// them for objects whose hashes collide with frequent objects. Such objects
+/// may be interned earlier than their real count warrants. Increasing the
+/// width of the sketch reduces collisions.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::CountMinInterner;
+///
+/// let mut interner = CountMinInterner::new(1024, 2);
+///
+/// let x = interner.intern("frequent");
+/// let y = interner.intern("frequent");
+/// assert!(!Rc::ptr_eq(&x, &y));
+///
+/// let z = interner.intern("frequent");
+/// assert!(Rc::ptr_eq(&z, &interner.intern("frequent")));
+///
+/// let w = interner.intern("rare");
+/// assert_eq!(interner.interner().try_intern(&"rare"), None);
+/// ```
+#[derive(Debug)]
+pub struct CountMinInterner<T> {
+    interner: RcInterner<T>,
+    counts: Vec<u32>,
+    width: usize,
+    threshold: u32,
+    hasher: RandomState,
+}
+
+impl<T: Hash + Eq> CountMinInterner<T> {
+    /// Create a new, empty interner with a count-min sketch of the given
+    /// width, interning objects whose estimated count exceeds `threshold`.
+    ///
+    /// # Panics
+    /// Panics if `width` is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountMinInterner;
+    /// let mut interner = CountMinInterner::new(1024, 2);
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new(width: usize, threshold: u32) -> CountMinInterner<T> {
+        assert!(width > 0, "count-min sketch width must not be zero");
+
+        CountMinInterner {
+            interner: RcInterner::new(),
+            counts: vec![0; width * DEPTH],
+            width,
+            threshold,
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Intern an owned object if it is seen frequently
+    ///
+    /// If the object has already been interned, the passed object will be
+    /// dropped, and a reference to the already interned object will be
+    /// returned.
+    ///
+    /// Otherwise, the estimated count of the object is incremented. If it now
+    /// exceeds the threshold, the passed object will be moved into an `Rc<T>`,
+    /// remembered for future calls to `intern()`, and returned. If not, the
+    /// passed object will be moved into an `Rc<T>` that is not remembered.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::CountMinInterner;
+    /// let mut interner = CountMinInterner::new(1024, 0);
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        if let Some(value) = self.interner.try_intern(&t) {
+            return value;
+        }
+
+        let mut estimate = u32::MAX;
+        for row in 0..DEPTH {
+            let hash = self.hasher.hash_one((row, &t));
+            let index = row * self.width + (hash % self.width as u64) as usize;
+            self.counts[index] = self.counts[index].saturating_add(1);
+            estimate = estimate.min(self.counts[index]);
+        }
+
+        if estimate > self.threshold {
+            self.interner.intern(t)
+        } else {
+            Rc::new(t)
+        }
+    }
+
+    /// Get a reference to the interner holding the frequently seen objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountMinInterner;
+    /// let mut interner = CountMinInterner::new(1024, 0);
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.interner().try_intern(&42), Some(x));
+    /// ```
+    pub fn interner(&self) -> &RcInterner<T> {
+        &self.interner
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// The estimated counts are not reset, so deallocated objects are interned
+    /// again the next time they are seen.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountMinInterner;
+    /// let mut interner = CountMinInterner::new(1024, 0);
+    ///
+    /// let x = interner.intern(42);
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.interner().try_intern(&42), None);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.interner.shrink_to_fit();
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountMinInterner;
+    /// let mut interner = CountMinInterner::new(1024, 1);
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(42);
+    /// interner.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        assert_eq!(self.counts.len(), self.width * DEPTH, "bad sketch");
+        self.interner.check_invariants();
+    }
+}
@@ -0,0 +1,205 @@
+use std::rc::Rc;
+use std::hash::Hash;
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// An interner returning reference-counted pointers to the interned data,
+/// which counts how often each object was interned
+///
+/// Every call to `intern()` or `intern_boxed()` increments the count of the
+/// interned object, whether it was already interned or not. The most
+/// frequently interned objects can be queried with `top_n()`. Like with
+/// `RcInterner`, interned objects will be deallocated when there are no
+/// references to them any more and `shrink_to_fit()` is called on the
+/// interner.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::CountingInterner;
+///
+/// let mut interner = CountingInterner::new();
+///
+/// let a = interner.intern("a");
+/// let b = interner.intern("b");
+/// let b = interner.intern("b");
+///
+/// assert_eq!(interner.count(&"a"), 1);
+/// assert_eq!(interner.count(&"b"), 2);
+/// assert_eq!(interner.count(&"c"), 0);
+/// ```
+#[derive(Debug)]
+pub struct CountingInterner<T: ?Sized> {
+    entries: HashMap<Rc<T>, Cell<u64>>,
+}
+
+impl<T: ?Sized> Default for CountingInterner<T> {
+    fn default() -> CountingInterner<T> {
+        CountingInterner {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<T: ?Sized + Hash + Eq> CountingInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountingInterner;
+    /// let mut interner = CountingInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> CountingInterner<T> {
+        Default::default()
+    }
+
+    /// Attempt to get a reference to an already interned object.
+    ///
+    /// If the object has not yet been interned, `None` will be returned. This
+    /// does not count as interning the object.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::CountingInterner;
+    /// let mut interner = CountingInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(Rc::new(42)));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// assert_eq!(interner.count(&42), 1);
+    /// ```
+    pub fn try_intern(&self, t: &T) -> Option<Rc<T>> {
+        self.entries.get_key_value(t).map(|(value, _)| value.clone())
+    }
+
+    /// Intern a boxed object, incrementing its count
+    ///
+    /// See `RcInterner::intern_boxed()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountingInterner;
+    /// let mut interner = CountingInterner::new();
+    ///
+    /// let x: Box<str> = Box::from("hello");
+    /// let y = interner.intern_boxed(x);
+    ///
+    /// assert_eq!(y.as_ref(), "hello");
+    /// assert_eq!(interner.count("hello"), 1);
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
+        if let Some(value) = self.hit(t.as_ref()) {
+            value
+        } else {
+            let value: Rc<T> = Rc::from(t);
+            self.entries.insert(value.clone(), Cell::new(1));
+            value
+        }
+    }
+
+    fn hit(&self, t: &T) -> Option<Rc<T>> {
+        let (value, count) = self.entries.get_key_value(t)?;
+        count.set(count.get() + 1);
+        Some(value.clone())
+    }
+
+    /// Get how often an object was interned.
+    ///
+    /// If the object has not yet been interned, or has been deallocated, the
+    /// count is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountingInterner;
+    /// let mut interner = CountingInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(42);
+    ///
+    /// assert_eq!(interner.count(&42), 2);
+    /// ```
+    pub fn count(&self, t: &T) -> u64 {
+        self.entries.get(t).map_or(0, Cell::get)
+    }
+
+    /// Get the `n` most frequently interned objects together with their
+    /// counts
+    ///
+    /// The objects are ordered by descending count. The order of objects with
+    /// equal counts is unspecified.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountingInterner;
+    /// let mut interner = CountingInterner::new();
+    ///
+    /// let mut handles = Vec::new();
+    /// for (s, n) in [("rare", 1), ("hot", 5), ("warm", 3)].iter() {
+    ///     for _ in 0..*n {
+    ///         handles.push(interner.intern(*s));
+    ///     }
+    /// }
+    ///
+    /// let top: Vec<_> = interner.top_n(2).into_iter()
+    ///     .map(|(value, count)| (**value, count))
+    ///     .collect();
+    /// assert_eq!(top, vec![("hot", 5), ("warm", 3)]);
+    /// ```
+    pub fn top_n(&self, n: usize) -> Vec<(&Rc<T>, u64)> {
+        let mut top: Vec<(&Rc<T>, u64)> = self.entries.iter()
+            .map(|(value, count)| (value, count.get()))
+            .collect();
+
+        top.sort_unstable_by_key(|&(_, count)| Reverse(count));
+        top.truncate(n);
+        top
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// The counts of deallocated objects are forgotten.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CountingInterner;
+    /// let mut interner = CountingInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.count(&42), 0);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.entries.retain(|value, _| Rc::strong_count(value) > 1);
+        self.entries.shrink_to_fit();
+    }
+}
+
+impl<T: Hash + Eq> CountingInterner<T> {
+    /// Intern an owned object, incrementing its count
+    ///
+    /// See `RcInterner::intern()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::CountingInterner;
+    /// let mut interner = CountingInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        if let Some(value) = self.hit(&t) {
+            value
+        } else {
+            let value = Rc::new(t);
+            self.entries.insert(value.clone(), Cell::new(1));
+            value
+        }
+    }
+}
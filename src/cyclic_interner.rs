@@ -0,0 +1,258 @@
+use std::rc::{Rc, Weak};
+use std::hash::{Hash, Hasher};
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+/// A node interned in a `CyclicInterner`
+///
+/// Each node holds its value and a list of edges to other nodes. Edges are
+/// stored as weak references, so cycles between nodes do not keep them alive.
+#[derive(Debug)]
+pub struct CyclicNode<T> {
+    value: T,
+    edges: RefCell<Vec<Weak<CyclicNode<T>>>>,
+}
+
+impl<T> CyclicNode<T> {
+    /// Get a reference to the value of this node.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    ///
+    /// let a = interner.intern("a");
+    /// assert_eq!(*a.value(), "a");
+    /// ```
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Get the nodes this node has edges to
+    ///
+    /// Edges to nodes that have been deallocated are skipped.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    ///
+    /// let a = interner.intern("a");
+    /// let b = interner.intern("b");
+    /// interner.link(&a, &b);
+    ///
+    /// assert!(Rc::ptr_eq(&a.edges()[0], &b));
+    /// ```
+    pub fn edges(&self) -> Vec<Rc<CyclicNode<T>>> {
+        self.edges.borrow().iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+#[derive(Debug)]
+struct Entry<T>(Rc<CyclicNode<T>>);
+
+impl<T: Hash> Hash for Entry<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.value.hash(state)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Entry<T> {
+    fn eq(&self, other: &Entry<T>) -> bool {
+        self.0.value == other.0.value
+    }
+}
+
+impl<T: Eq> Eq for Entry<T> {}
+
+impl<T> Borrow<T> for Entry<T> {
+    fn borrow(&self) -> &T {
+        &self.0.value
+    }
+}
+
+/// An interner for graph nodes that may reference each other in cycles
+///
+/// Nodes are deduplicated by their value. Edges between nodes are stored as
+/// weak references, so cyclic graphs do not leak. Like with `RcInterner`,
+/// interned nodes will be deallocated when there are no references to them
+/// any more and `shrink_to_fit()` is called on the interner.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::CyclicInterner;
+///
+/// let mut interner = CyclicInterner::new();
+///
+/// let a = interner.intern("a");
+/// let b = interner.intern("b");
+/// interner.link(&a, &b);
+/// interner.link(&b, &a);
+///
+/// assert!(Rc::ptr_eq(&a, &interner.intern("a")));
+/// assert!(Rc::ptr_eq(&a.edges()[0].edges()[0], &a));
+///
+/// drop(a);
+/// drop(b);
+///
+/// interner.shrink_to_fit();
+/// assert!(interner.resolve(&"a").is_none());
+/// assert!(interner.resolve(&"b").is_none());
+/// ```
+#[derive(Debug)]
+pub struct CyclicInterner<T>(HashSet<Entry<T>>);
+
+impl<T> Default for CyclicInterner<T> {
+    fn default() -> CyclicInterner<T> {
+        CyclicInterner(HashSet::new())
+    }
+}
+
+impl<T: Hash + Eq> CyclicInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> CyclicInterner<T> {
+        Default::default()
+    }
+
+    /// Intern a node with the given value
+    ///
+    /// If a node with this value has already been interned, the passed value
+    /// will be dropped, and a reference to the already interned node will be
+    /// returned.
+    ///
+    /// If no node with this value has been interned yet, a new node without
+    /// any edges will be created, remembered for future calls to `intern()`,
+    /// and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<CyclicNode<T>> {
+        if let Some(node) = self.resolve(&t) {
+            node
+        } else {
+            let node = Rc::new(CyclicNode {
+                value: t,
+                edges: RefCell::new(Vec::new()),
+            });
+            self.0.insert(Entry(node.clone()));
+            node
+        }
+    }
+
+    /// Attempt to get a reference to an already interned node.
+    ///
+    /// If a node with this value has already been interned, an option
+    /// containing a reference to the node will be returned.
+    ///
+    /// If no node with this value has been interned yet, `None` will be
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(interner.resolve(&42).is_some());
+    /// assert!(interner.resolve(&1337).is_none());
+    /// ```
+    pub fn resolve(&self, t: &T) -> Option<Rc<CyclicNode<T>>> {
+        self.0.get(t).map(|entry| entry.0.clone())
+    }
+
+    /// Add an edge from one interned node to another.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    ///
+    /// let a = interner.intern("a");
+    /// interner.link(&a, &a);
+    ///
+    /// assert_eq!(*a.edges()[0].value(), "a");
+    /// ```
+    pub fn link(&mut self, from: &Rc<CyclicNode<T>>, to: &Rc<CyclicNode<T>>) {
+        from.edges.borrow_mut().push(Rc::downgrade(to));
+    }
+
+    /// Deallocate all interned nodes that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// Since edges do not keep nodes alive, nodes that are only referenced by
+    /// edges of other nodes are deallocated as well.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    ///
+    /// let a = interner.intern("a");
+    /// let b = interner.intern("b");
+    /// interner.link(&a, &b);
+    ///
+    /// drop(b);
+    /// interner.shrink_to_fit();
+    ///
+    /// assert!(interner.resolve(&"b").is_none());
+    /// assert!(a.edges().is_empty());
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.0.retain(|entry| Rc::strong_count(&entry.0) > 1);
+        self.0.shrink_to_fit();
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CyclicInterner;
+    /// let mut interner = CyclicInterner::new();
+    ///
+    /// let a = interner.intern("a");
+    /// let b = interner.intern("b");
+    /// interner.link(&a, &b);
+    ///
+    /// drop(b);
+    /// interner.check_invariants();
+    ///
+    /// interner.shrink_to_fit();
+    /// interner.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        for entry in &self.0 {
+            let found = self.0.get(&entry.0.value)
+                .expect("interned node not found by lookup");
+            assert!(Rc::ptr_eq(&found.0, &entry.0), "duplicate node");
+
+            for edge in entry.0.edges.borrow().iter() {
+                if let Some(target) = edge.upgrade() {
+                    let found = self.0.get(&target.value)
+                        .expect("referenced node not interned");
+                    assert!(Rc::ptr_eq(&found.0, &target), "stale edge");
+                }
+            }
+        }
+    }
+}
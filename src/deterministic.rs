@@ -0,0 +1,166 @@
+use std::mem;
+use std::hash::{Hash, BuildHasher};
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+
+/// An interner storing interned objects in a bump arena at reproducible
+/// offsets
+///
+/// Interned objects are appended to a single arena in the order they are
+/// first interned, and identified by their byte offset from the start of the
+/// arena. Interning the same sequence of objects therefore always yields the
+/// same offsets, independent of the heap layout of the process, which makes
+/// bugs depending on object identity or ordering by address reproducible.
+///
+/// Interned objects are never deallocated.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::DeterministicInterner;
+///
+/// let mut interner = DeterministicInterner::new();
+///
+/// let x = interner.intern(42u64);
+/// let y = interner.intern(1337u64);
+///
+/// assert_eq!(x, 0);
+/// assert_eq!(y, 8);
+/// assert_eq!(interner.intern(42), x);
+/// assert_eq!(interner.get(y), Some(&1337));
+/// ```
+#[derive(Debug)]
+pub struct DeterministicInterner<T> {
+    arena: Vec<T>,
+    index: HashMap<u64, Vec<usize>>,
+    hasher: RandomState,
+}
+
+impl<T> Default for DeterministicInterner<T> {
+    fn default() -> DeterministicInterner<T> {
+        DeterministicInterner {
+            arena: Vec::new(),
+            index: HashMap::new(),
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq> DeterministicInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::DeterministicInterner;
+    /// let mut interner = DeterministicInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> DeterministicInterner<T> {
+        Default::default()
+    }
+
+    /// Intern an owned object, returning its offset in the arena
+    ///
+    /// If the object has already been interned, the passed object will be
+    /// dropped, and the offset of the already interned object will be
+    /// returned.
+    ///
+    /// If the object has not yet been interned, the passed object will be
+    /// appended to the arena, and its offset will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::DeterministicInterner;
+    /// let sequence = ["a", "b", "a", "c", "b"];
+    ///
+    /// let mut first = DeterministicInterner::new();
+    /// let mut second = DeterministicInterner::new();
+    ///
+    /// let x: Vec<_> = sequence.iter().map(|s| first.intern(*s)).collect();
+    /// let y: Vec<_> = sequence.iter().map(|s| second.intern(*s)).collect();
+    ///
+    /// assert_eq!(x, y);
+    /// assert_eq!(x[0], x[2]);
+    /// ```
+    pub fn intern(&mut self, t: T) -> usize {
+        if let Some(offset) = self.offset_of(&t) {
+            return offset;
+        }
+
+        let hash = self.hasher.hash_one(&t);
+        let index = self.arena.len();
+        self.arena.push(t);
+        self.index.entry(hash).or_default().push(index);
+        index * mem::size_of::<T>()
+    }
+
+    /// Get the offset of an already interned object in the arena.
+    ///
+    /// If the object has not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::DeterministicInterner;
+    /// let mut interner = DeterministicInterner::new();
+    ///
+    /// let x = interner.intern(42u32);
+    /// let y = interner.intern(1337u32);
+    ///
+    /// assert_eq!(interner.offset_of(&1337), Some(4));
+    /// assert_eq!(interner.offset_of(&0), None);
+    /// ```
+    pub fn offset_of(&self, t: &T) -> Option<usize> {
+        let indices = self.index.get(&self.hasher.hash_one(t))?;
+        let index = indices.iter().copied().find(|&i| self.arena[i] == *t)?;
+        Some(index * mem::size_of::<T>())
+    }
+
+    /// Get the interned object at an offset in the arena.
+    ///
+    /// If no object has been interned at this offset, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::DeterministicInterner;
+    /// let mut interner = DeterministicInterner::new();
+    ///
+    /// let x = interner.intern(42u32);
+    /// assert_eq!(interner.get(x), Some(&42));
+    /// assert_eq!(interner.get(x + 1), None);
+    /// ```
+    pub fn get(&self, offset: usize) -> Option<&T> {
+        match mem::size_of::<T>() {
+            0 => self.arena.first().filter(|_| offset == 0),
+            size if offset.is_multiple_of(size) => {
+                self.arena.get(offset / size)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the number of interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::DeterministicInterner;
+    /// let mut interner = DeterministicInterner::new();
+    ///
+    /// interner.intern(42);
+    /// interner.intern(42);
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Check whether no objects have been interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::DeterministicInterner;
+    /// let interner = DeterministicInterner::<u32>::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.arena.is_empty()
+    }
+}
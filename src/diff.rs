@@ -0,0 +1,13 @@
+/// The difference between the contents of two interners
+///
+/// Returned by `RcInterner::diff()` and `ArcInterner::diff()`, with `H`
+/// being the handle type of the interner. Both lists are in arbitrary order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternerDiff<H> {
+    /// Handles to the objects that are interned in the first interner, but
+    /// not in the second.
+    pub added: Vec<H>,
+    /// Handles to the objects that are interned in the second interner, but
+    /// not in the first.
+    pub removed: Vec<H>,
+}
@@ -0,0 +1,207 @@
+use std::rc::Rc;
+use std::hash::{Hash, Hasher};
+use std::borrow::Borrow;
+use std::ffi::{CStr, CString, NulError};
+use std::os::raw::c_char;
+use std::collections::HashMap;
+
+/// A C string compared and hashed by its bytes without the nul terminator,
+/// so that it can be looked up by a string slice
+#[derive(Debug)]
+struct Key(Rc<CStr>);
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bytes().hash(state)
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        self.0.to_bytes() == other.0.to_bytes()
+    }
+}
+
+impl Eq for Key {}
+
+impl Borrow<[u8]> for Key {
+    fn borrow(&self) -> &[u8] {
+        self.0.to_bytes()
+    }
+}
+
+/// A string interner returning stable integer handles for use across an FFI
+/// boundary
+///
+/// Interned strings are stored as nul-terminated C strings. Each distinct
+/// string gets a `u64` handle, which can be passed to C code and resolved to
+/// a pointer to the C string with `resolve()`. Handles are never reused, even
+/// after they have been released, so a stale handle can never resolve to a
+/// different string.
+///
+/// Unlike `RcInterner`, interned strings are not reference counted by the
+/// caller; they stay alive until their handle is released with `release()` or
+/// the interner is dropped.
+///
+/// # Example
+/// ```rust
+/// # use std::ffi::CStr;
+/// use refcount_interner::FfiStrInterner;
+///
+/// let mut interner = FfiStrInterner::new();
+///
+/// let x = interner.intern("hello")?;
+/// let y = interner.intern("world")?;
+///
+/// assert_eq!(x, interner.intern("hello")?);
+/// assert_ne!(x, y);
+///
+/// let ptr = interner.resolve(x).unwrap();
+/// assert_eq!(unsafe { CStr::from_ptr(ptr) }.to_str(), Ok("hello"));
+/// # Ok::<(), std::ffi::NulError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct FfiStrInterner {
+    handles: HashMap<Key, u64>,
+    strings: HashMap<u64, Rc<CStr>>,
+    next: u64,
+}
+
+impl FfiStrInterner {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::FfiStrInterner;
+    /// let mut interner = FfiStrInterner::new();
+    /// # let x = interner.intern("hello");
+    /// ```
+    pub fn new() -> FfiStrInterner {
+        Default::default()
+    }
+
+    /// Intern a string slice, returning its handle
+    ///
+    /// If the string slice has already been interned and its handle has not
+    /// been released, the handle of the already interned string slice will be
+    /// returned.
+    ///
+    /// Otherwise the string slice is copied into a new C string and a new
+    /// handle is returned. The string slice is only copied if it has not been
+    /// interned yet.
+    ///
+    /// # Errors
+    /// Returns an error if the string slice contains a nul byte, since it
+    /// cannot be represented as a C string.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::FfiStrInterner;
+    /// let mut interner = FfiStrInterner::new();
+    ///
+    /// let x = interner.intern("hello")?;
+    /// assert_eq!(x, interner.intern("hello")?);
+    /// assert!(interner.intern("hel\0lo").is_err());
+    /// # Ok::<(), std::ffi::NulError>(())
+    /// ```
+    pub fn intern(&mut self, s: &str) -> Result<u64, NulError> {
+        // a string slice with a nul byte is never found, since no interned
+        // C string contains one, and is rejected by CString::new() below
+        if let Some(&handle) = self.handles.get(s.as_bytes()) {
+            return Ok(handle);
+        }
+
+        let string: Rc<CStr> = Rc::from(CString::new(s)?);
+
+        let handle = self.next;
+        self.next += 1;
+
+        self.handles.insert(Key(string.clone()), handle);
+        self.strings.insert(handle, string);
+        Ok(handle)
+    }
+
+    /// Get a pointer to the nul-terminated C string of a handle
+    ///
+    /// The pointer stays valid until the handle is released or the interner
+    /// is dropped. If the handle was not returned by this interner or has
+    /// been released, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::ffi::CStr;
+    /// # use refcount_interner::FfiStrInterner;
+    /// let mut interner = FfiStrInterner::new();
+    ///
+    /// let x = interner.intern("hello")?;
+    /// let ptr = interner.resolve(x).unwrap();
+    ///
+    /// interner.intern("world")?;
+    /// assert_eq!(interner.resolve(x), Some(ptr));
+    /// assert_eq!(unsafe { CStr::from_ptr(ptr) }.to_bytes(), b"hello");
+    /// assert_eq!(interner.resolve(42), None);
+    /// # Ok::<(), std::ffi::NulError>(())
+    /// ```
+    pub fn resolve(&self, handle: u64) -> Option<*const c_char> {
+        self.strings.get(&handle).map(|string| string.as_ptr())
+    }
+
+    /// Release a handle, deallocating its C string
+    ///
+    /// Pointers returned by `resolve()` for this handle are dangling after
+    /// this call. Interning the same string again returns a new handle.
+    ///
+    /// Returns whether the handle was interned and not yet released.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::FfiStrInterner;
+    /// let mut interner = FfiStrInterner::new();
+    ///
+    /// let x = interner.intern("hello")?;
+    /// assert!(interner.release(x));
+    /// assert!(!interner.release(x));
+    ///
+    /// assert_eq!(interner.resolve(x), None);
+    /// assert_ne!(interner.intern("hello")?, x);
+    /// # Ok::<(), std::ffi::NulError>(())
+    /// ```
+    pub fn release(&mut self, handle: u64) -> bool {
+        match self.strings.remove(&handle) {
+            Some(string) => {
+                self.handles.remove(string.to_bytes());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the number of interned strings whose handles have not been
+    /// released.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::FfiStrInterner;
+    /// let mut interner = FfiStrInterner::new();
+    ///
+    /// interner.intern("hello")?;
+    /// interner.intern("hello")?;
+    /// assert_eq!(interner.len(), 1);
+    /// # Ok::<(), std::ffi::NulError>(())
+    /// ```
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Check whether no strings are interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::FfiStrInterner;
+    /// let interner = FfiStrInterner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A handle to an interned object, tagged with the generation of the interner
+/// it was created in
+///
+/// Generation-tagged handles are created with `tag()` on `RcInterner` or
+/// `ArcInterner`, and keep the interned object alive. The generation of an
+/// interner changes whenever interned objects are removed or replaced, e.g. by
+/// `shrink_to_fit()`, `remove()` or `clear()`. Use `validate()` on the
+/// interner to check whether the handle still refers to the interned object.
+///
+/// # Example
+/// ```rust
+/// use std::rc::Rc;
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+///
+/// let x = interner.intern(42);
+/// let handle = interner.tag(&x);
+/// assert!(interner.validate(&handle));
+///
+/// drop(x);
+/// interner.clear();
+/// let y = interner.intern(42);
+/// interner.shrink_to_fit();
+///
+/// assert!(!interner.validate(&handle));
+/// assert!(!Rc::ptr_eq(handle.get(), &y));
+/// assert!(handle.generation() < interner.generation());
+/// ```
+#[derive(Debug, Clone)]
+pub struct GenTaggedHandle<P> {
+    pub(crate) value: P,
+    pub(crate) generation: u64,
+    #[cfg(debug_assertions)]
+    pub(crate) interner: u64,
+}
+
+impl<P> GenTaggedHandle<P> {
+    /// Get the generation of the interner at the time this handle was
+    /// created.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let handle = interner.tag(&x);
+    ///
+    /// assert_eq!(handle.generation(), interner.generation());
+    /// ```
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Get a reference to the interned object
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let handle = interner.tag(&x);
+    ///
+    /// assert!(Rc::ptr_eq(handle.get(), &x));
+    /// ```
+    pub fn get(&self) -> &P {
+        &self.value
+    }
+
+    /// Unwrap the reference to the interned object
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let handle = interner.tag(&x);
+    ///
+    /// assert!(Rc::ptr_eq(&handle.into_inner(), &x));
+    /// ```
+    pub fn into_inner(self) -> P {
+        self.value
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) fn next_interner_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
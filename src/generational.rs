@@ -0,0 +1,234 @@
+//! An interner addressing interned objects by generational indices
+//!
+//! See `GenerationalInterner`.
+
+use std::rc::Rc;
+use std::hash::Hash;
+use std::convert::TryFrom;
+use std::collections::HashMap;
+
+/// A generational index of an object interned into a `GenerationalInterner`
+///
+/// A handle consists of the index of a slot and the generation of the slot
+/// at the time the object was interned. When the object is deallocated, the
+/// generation of its slot is increased, so the handle no longer resolves,
+/// even after the slot has been reused for another object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: u32,
+    generation: u32,
+}
+
+impl Handle {
+    /// Get the index of the slot of this handle.
+    pub fn index(self) -> u32 {
+        self.index
+    }
+
+    /// Get the generation of the slot of this handle.
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    value: Option<Rc<T>>,
+    generation: u32,
+}
+
+/// An interner returning reference-counted pointers to the interned data
+/// together with generational indices
+///
+/// Every interned object is stored in a slot, and can be resolved by a
+/// `Handle` consisting of the slot index and the slot generation. Handles are
+/// `Copy` and compact, which makes them suitable for storing in large tables,
+/// as in entity component systems.
+///
+/// Like with `RcInterner`, interned objects will be deallocated when there are
+/// no `Rc<T>` references to them any more and `shrink_to_fit()` is called on
+/// the interner. Handles do not keep interned objects alive. The slots of
+/// deallocated objects are reused, and their generation is increased, so that
+/// stale handles are detected.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::generational::GenerationalInterner;
+///
+/// let mut interner = GenerationalInterner::new();
+///
+/// let (x, x_rc) = interner.intern("x");
+/// let (y, y_rc) = interner.intern("y");
+/// assert_eq!(interner.intern("x").0, x);
+///
+/// drop(x_rc);
+/// interner.shrink_to_fit();
+///
+/// let (z, z_rc) = interner.intern("z");
+/// assert_eq!(z.index(), x.index());
+/// assert_eq!(interner.resolve(x), None);
+/// assert_eq!(interner.resolve(y), Some(&"y"));
+/// assert_eq!(interner.resolve(z), Some(&"z"));
+/// ```
+#[derive(Debug)]
+pub struct GenerationalInterner<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    indices: HashMap<Rc<T>, u32>,
+}
+
+impl<T> Default for GenerationalInterner<T> {
+    fn default() -> GenerationalInterner<T> {
+        GenerationalInterner {
+            slots: Vec::new(),
+            free: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq> GenerationalInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::generational::GenerationalInterner;
+    /// let mut interner = GenerationalInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> GenerationalInterner<T> {
+        Default::default()
+    }
+
+    /// Intern an owned object, returning its handle and a reference to it
+    ///
+    /// If the object has already been interned, the passed object will be
+    /// dropped, and the handle of and a reference to the already interned
+    /// object will be returned.
+    ///
+    /// If the object has not yet been interned, it will be moved into an
+    /// `Rc<T>` and stored in a free slot.
+    ///
+    /// # Panics
+    /// Panics if the number of slots would exceed `u32::MAX`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::generational::GenerationalInterner;
+    /// let mut interner = GenerationalInterner::new();
+    ///
+    /// let (x, x_rc) = interner.intern(42);
+    /// let (y, y_rc) = interner.intern(42);
+    ///
+    /// assert_eq!(x, y);
+    /// assert!(Rc::ptr_eq(&x_rc, &y_rc));
+    /// ```
+    pub fn intern(&mut self, t: T) -> (Handle, Rc<T>) {
+        if let Some((value, &index)) = self.indices.get_key_value(&t) {
+            let generation = self.slots[index as usize].generation;
+            return (Handle { index, generation }, value.clone());
+        }
+
+        let value = Rc::new(t);
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                let index = u32::try_from(self.slots.len())
+                    .expect("too many slots");
+                self.slots.push(Slot { value: None, generation: 0 });
+                index
+            }
+        };
+
+        let slot = &mut self.slots[index as usize];
+        slot.value = Some(value.clone());
+        self.indices.insert(value.clone(), index);
+        (Handle { index, generation: slot.generation }, value)
+    }
+
+    /// Get a reference to the interned object of a handle
+    ///
+    /// If the object of the handle has been deallocated, or the handle was not
+    /// returned by this interner, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::generational::GenerationalInterner;
+    /// let mut interner = GenerationalInterner::new();
+    ///
+    /// let (x, x_rc) = interner.intern(42);
+    /// assert_eq!(interner.resolve(x), Some(&42));
+    ///
+    /// drop(x_rc);
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.resolve(x), None);
+    /// ```
+    pub fn resolve(&self, handle: Handle) -> Option<&T> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+
+        slot.value.as_deref()
+    }
+
+    /// Get the number of interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::generational::GenerationalInterner;
+    /// let mut interner = GenerationalInterner::new();
+    ///
+    /// interner.intern(42);
+    /// interner.intern(42);
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Check whether no objects are interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::generational::GenerationalInterner;
+    /// let interner = GenerationalInterner::<u32>::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Deallocate all interned objects that are no longer referenced, freeing
+    /// their slots and increasing the generation of the slots.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::generational::GenerationalInterner;
+    /// let mut interner = GenerationalInterner::new();
+    ///
+    /// let (x, _) = interner.intern(42);
+    /// interner.shrink_to_fit();
+    ///
+    /// let (y, _) = interner.intern(42);
+    /// assert_eq!(y.index(), x.index());
+    /// assert_eq!(y.generation(), x.generation() + 1);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        let slots = &mut self.slots;
+        let free = &mut self.free;
+        self.indices.retain(|value, &mut index| {
+            if Rc::strong_count(value) > 2 {
+                return true;
+            }
+
+            let slot = &mut slots[index as usize];
+            slot.value = None;
+            slot.generation = slot.generation.wrapping_add(1);
+            free.push(index);
+            false
+        });
+        self.indices.shrink_to_fit();
+    }
+}
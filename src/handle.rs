@@ -0,0 +1,203 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::fmt;
+use std::ops::Deref;
+use std::hash::{Hash, Hasher};
+
+use crate::refcount::RefCount;
+
+/// An opaque handle to an object interned into a `RefCountInterner`
+///
+/// Returned by `RefCountInterner::intern_opaque()`. Unlike the `P` returned
+/// by the other intern methods, the representation of the handle is not part
+/// of the API, so it may change in the future without breaking callers.
+///
+/// Handles are cheap to clone, dereference to the interned object, and compare
+/// and hash like the interned object. `Handle<T>` and `SyncHandle<T>` are the
+/// handles of `RcInterner` and `ArcInterner` respectively.
+///
+/// # Example
+/// ```rust
+/// use std::rc::Rc;
+/// use std::sync::Arc;
+/// use refcount_interner::{RcInterner, ArcInterner, RefCountHandle};
+///
+/// let mut rc_interner = RcInterner::<str>::new();
+/// let mut arc_interner = ArcInterner::<str>::new();
+///
+/// let x: RefCountHandle<Rc<str>> = rc_interner.intern_opaque("hello");
+/// let y: RefCountHandle<Arc<str>> = arc_interner.intern_opaque("hello");
+///
+/// assert_eq!(&*x, &*y);
+/// ```
+pub struct RefCountHandle<P> where P: RefCount {
+    pub(crate) value: P,
+    #[cfg(debug_assertions)]
+    pub(crate) interner_id: u64,
+}
+
+/// An opaque handle to an object interned into an `RcInterner`
+///
+/// Returned by `RcInterner::intern_opaque()`. This is `RefCountHandle`
+/// wrapping an `Rc<T>`.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::{RcInterner, Handle};
+///
+/// let mut interner = RcInterner::<str>::new();
+///
+/// let x: Handle<str> = interner.intern_opaque("hello");
+/// let y = interner.intern_opaque(String::from("hello"));
+///
+/// assert_eq!(&*x, "hello");
+/// assert_eq!(x, y);
+/// assert!(Handle::ptr_eq(&x, &y.clone()));
+/// ```
+pub type Handle<T> = RefCountHandle<Rc<T>>;
+
+/// An opaque handle to an object interned into an `ArcInterner`
+///
+/// Returned by `ArcInterner::intern_opaque()`. Like `Handle<T>`, but can be
+/// sent to other threads if `T` is `Send` and `Sync`.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::{ArcInterner, SyncHandle};
+///
+/// let mut interner = ArcInterner::<str>::new();
+///
+/// let x: SyncHandle<str> = interner.intern_opaque("hello");
+/// let y = interner.intern_opaque("hello");
+///
+/// std::thread::spawn(move || assert_eq!(&*x, "hello")).join().unwrap();
+/// assert_eq!(&*y, "hello");
+/// ```
+///
+/// Comparing two handles by pointer:
+/// ```rust
+/// # use refcount_interner::{ArcInterner, SyncHandle};
+/// let mut interner = ArcInterner::new();
+///
+/// let x = interner.intern_opaque(42);
+/// let y = interner.intern_opaque(42);
+///
+/// assert!(SyncHandle::ptr_eq(&x, &y));
+/// ```
+pub type SyncHandle<T> = RefCountHandle<Arc<T>>;
+
+impl<P: RefCount> RefCountHandle<P> {
+    /// Check whether two handles refer to the same interned object.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, Handle};
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_opaque(42);
+    /// let y = interner.intern_opaque(42);
+    ///
+    /// assert!(Handle::ptr_eq(&x, &y));
+    /// ```
+    pub fn ptr_eq(this: &RefCountHandle<P>, other: &RefCountHandle<P>) -> bool {
+        P::ptr_eq(&this.value, &other.value)
+    }
+}
+
+impl<P: RefCount> Clone for RefCountHandle<P> {
+    fn clone(&self) -> RefCountHandle<P> {
+        RefCountHandle {
+            value: self.value.clone(),
+            #[cfg(debug_assertions)]
+            interner_id: self.interner_id,
+        }
+    }
+}
+
+impl<P: RefCount> Deref for RefCountHandle<P> {
+    type Target = P::Target;
+
+    fn deref(&self) -> &P::Target {
+        &self.value
+    }
+}
+
+impl<P: RefCount> AsRef<P::Target> for RefCountHandle<P> {
+    fn as_ref(&self) -> &P::Target {
+        &self.value
+    }
+}
+
+impl<P> PartialEq for RefCountHandle<P>
+    where P: RefCount, P::Target: PartialEq
+{
+    fn eq(&self, other: &RefCountHandle<P>) -> bool {
+        RefCountHandle::ptr_eq(self, other) || *self.value == *other.value
+    }
+}
+
+impl<P> Eq for RefCountHandle<P> where P: RefCount, P::Target: Eq {}
+
+impl<P> Hash for RefCountHandle<P> where P: RefCount, P::Target: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (*self.value).hash(state)
+    }
+}
+
+impl<P> fmt::Debug for RefCountHandle<P>
+    where P: RefCount, P::Target: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (*self.value).fmt(f)
+    }
+}
+
+impl<P> fmt::Display for RefCountHandle<P>
+    where P: RefCount, P::Target: fmt::Display
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (*self.value).fmt(f)
+    }
+}
+
+/// A reference to an object interned into a `RefCountInterner<P>`
+///
+/// This is implemented for `P` itself and for the opaque `RefCountHandle<P>`,
+/// so that both can be passed to `RefCountInterner::is_canonical()`. Opaque
+/// handles remember the interner that returned them in debug builds, which
+/// allows checking that a handle is not used with a different interner.
+pub trait InternedRef<P> {
+    /// Get the reference-counted pointer to the object.
+    fn pointer(&self) -> &P;
+
+    /// Get the id of the interner that returned this reference, if known.
+    ///
+    /// This is always `None` in release builds.
+    fn interner_id(&self) -> Option<u64>;
+}
+
+impl<P: RefCount> InternedRef<P> for P {
+    fn pointer(&self) -> &P {
+        self
+    }
+
+    fn interner_id(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl<P: RefCount> InternedRef<P> for RefCountHandle<P> {
+    fn pointer(&self) -> &P {
+        &self.value
+    }
+
+    #[cfg(debug_assertions)]
+    fn interner_id(&self) -> Option<u64> {
+        Some(self.interner_id)
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn interner_id(&self) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+use std::collections::VecDeque;
+
+/// Tracks the hit rate of the last intern calls in a sliding window and calls
+/// a callback after every call that leaves the hit rate below a threshold
+pub(crate) struct HitRateMonitor<F: ?Sized> {
+    window: usize,
+    threshold: f64,
+    recent: VecDeque<bool>,
+    hits: usize,
+    callback: Box<F>,
+}
+
+impl<F: ?Sized + FnMut(f64)> HitRateMonitor<F> {
+    pub(crate) fn new(window: usize, threshold: f64, callback: Box<F>)
+        -> HitRateMonitor<F>
+    {
+        assert!(window > 0, "hit rate window must not be empty");
+
+        HitRateMonitor {
+            window,
+            threshold,
+            recent: VecDeque::with_capacity(window),
+            hits: 0,
+            callback,
+        }
+    }
+
+    pub(crate) fn record(&mut self, hit: bool) {
+        if self.recent.len() == self.window
+            && self.recent.pop_front() == Some(true)
+        {
+            self.hits -= 1;
+        }
+
+        self.recent.push_back(hit);
+        if hit {
+            self.hits += 1;
+        }
+
+        if self.recent.len() == self.window {
+            let rate = self.hits as f64 / self.window as f64;
+            if rate < self.threshold {
+                (self.callback)(rate);
+            }
+        }
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for HitRateMonitor<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HitRateMonitor")
+            .field("window", &self.window)
+            .field("threshold", &self.threshold)
+            .field("hits", &self.hits)
+            .field("calls", &self.recent.len())
+            .finish()
+    }
+}
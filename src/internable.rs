@@ -0,0 +1,95 @@
+use std::hash::Hash;
+
+use crate::{RefCount, RefCountInterner, SetBackend};
+
+/// A value that can be interned into an interner of `T`
+///
+/// This is implemented for owned objects, which are interned as-is, and for
+/// the borrowed and owned forms of string slices and slices, which are
+/// interned with the matching `intern_*()` method. It allows generic code to
+/// intern values with `intern_any()` without choosing the method itself.
+///
+/// The interned type is a type parameter rather than an associated type, so
+/// that e.g. `&str` can be interned both as `str` and as `&str`.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::{RcInterner, Internable};
+///
+/// fn intern_both<A, B>(interner: &mut RcInterner<str>, a: A, b: B) -> bool
+///     where A: Internable<str>, B: Internable<str>
+/// {
+///     Rc::ptr_eq(&interner.intern_any(a), &interner.intern_any(b))
+/// }
+///
+/// let mut interner = RcInterner::new();
+/// assert!(intern_both(&mut interner, "x", String::from("x")));
+/// ```
+pub trait Internable<T: ?Sized> {
+    /// Intern this value into a `RefCountInterner`.
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, T, B>) -> P
+        where P: RefCount<Target = T>, B: SetBackend<P>;
+}
+
+impl<T: Hash + Eq> Internable<T> for T {
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, T, B>) -> P
+        where P: RefCount<Target = T>, B: SetBackend<P>
+    {
+        interner.intern(self)
+    }
+}
+
+impl Internable<str> for &str {
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, str, B>)
+        -> P
+        where P: RefCount<Target = str>, B: SetBackend<P>
+    {
+        interner.get_or_intern_boxed_with(self, || Box::from(self))
+    }
+}
+
+impl Internable<str> for String {
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, str, B>)
+        -> P
+        where P: RefCount<Target = str>, B: SetBackend<P>
+    {
+        interner.intern_boxed(self.into_boxed_str())
+    }
+}
+
+impl Internable<str> for Box<str> {
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, str, B>)
+        -> P
+        where P: RefCount<Target = str>, B: SetBackend<P>
+    {
+        interner.intern_boxed(self)
+    }
+}
+
+impl<T: Hash + Eq + Clone> Internable<[T]> for &[T] {
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, [T], B>)
+        -> P
+        where P: RefCount<Target = [T]>, B: SetBackend<P>
+    {
+        interner.get_or_intern_boxed_with(self, || Box::from(self))
+    }
+}
+
+impl<T: Hash + Eq + Clone> Internable<[T]> for Vec<T> {
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, [T], B>)
+        -> P
+        where P: RefCount<Target = [T]>, B: SetBackend<P>
+    {
+        interner.intern_boxed(self.into_boxed_slice())
+    }
+}
+
+impl<T: Hash + Eq + Clone> Internable<[T]> for Box<[T]> {
+    fn intern_into<P, B>(self, interner: &mut RefCountInterner<P, [T], B>)
+        -> P
+        where P: RefCount<Target = [T]>, B: SetBackend<P>
+    {
+        interner.intern_boxed(self)
+    }
+}
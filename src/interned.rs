@@ -0,0 +1,77 @@
+use std::rc::Rc;
+use std::ops::Deref;
+use std::borrow::Borrow;
+
+/// A handle to an interned object, usable as a key in generic collections
+///
+/// `Interned<T>` wraps the `Rc<T>` returned by `RcInterner`. It compares and
+/// hashes by value like `T`, and implements `Borrow<T>`, so collections keyed
+/// by `Interned<T>` can be queried with a plain `&T`.
+///
+/// # Example
+/// ```rust
+/// # use std::collections::HashMap;
+/// use refcount_interner::{RcInterner, Interned};
+///
+/// let mut interner = RcInterner::new();
+/// let mut map = HashMap::new();
+///
+/// let key = Interned::from(interner.intern_str("hello"));
+/// map.insert(key, 42);
+///
+/// assert_eq!(map.get("hello"), Some(&42));
+/// assert_eq!(map.get("world"), None);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interned<T: ?Sized>(Rc<T>);
+
+impl<T: ?Sized> Interned<T> {
+    /// Get the `Rc<T>` wrapped by this handle.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::{RcInterner, Interned};
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = Interned::from(x.clone());
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y.into_inner()));
+    /// ```
+    pub fn into_inner(self) -> Rc<T> {
+        self.0
+    }
+}
+
+impl<T: ?Sized> Clone for Interned<T> {
+    fn clone(&self) -> Interned<T> {
+        Interned(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> From<Rc<T>> for Interned<T> {
+    fn from(value: Rc<T>) -> Interned<T> {
+        Interned(value)
+    }
+}
+
+impl<T: ?Sized> Deref for Interned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for Interned<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for Interned<T> {
+    fn borrow(&self) -> &T {
+        &self.0
+    }
+}
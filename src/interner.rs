@@ -0,0 +1,68 @@
+use std::ops::Deref;
+
+/// A common interface for interners, allowing code to be generic over the
+/// kind of interner used
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::{Interner, RcInterner, ArcInterner};
+///
+/// fn intern_all<I: Interner<u32>>(interner: &mut I, values: &[u32]) {
+///     interner.reserve(values.len());
+///     for &value in values {
+///         interner.intern(value);
+///     }
+/// }
+///
+/// let mut rc_interner = RcInterner::new();
+/// intern_all(&mut rc_interner, &[1, 2, 3, 2, 1]);
+/// assert_eq!(Interner::len(&rc_interner), 3);
+///
+/// let mut arc_interner = ArcInterner::new();
+/// intern_all(&mut arc_interner, &[1, 2, 3, 2, 1]);
+/// assert_eq!(Interner::len(&arc_interner), 3);
+/// ```
+pub trait Interner<T: ?Sized> {
+    /// The reference-counted pointer type returned by the interner
+    type Handle: Deref<Target = T> + Clone;
+
+    /// Attempt to get a reference to an already interned object.
+    fn try_intern(&self, t: &T) -> Option<Self::Handle>;
+
+    /// Intern a boxed object.
+    fn intern_boxed(&mut self, t: Box<T>) -> Self::Handle;
+
+    /// Intern an owned object.
+    ///
+    /// The default implementation boxes the object and calls
+    /// `intern_boxed()`.
+    fn intern(&mut self, t: T) -> Self::Handle where T: Sized {
+        self.intern_boxed(Box::new(t))
+    }
+
+    /// Get the number of interned objects, including objects that are no
+    /// longer referenced but have not been deallocated yet.
+    fn len(&self) -> usize;
+
+    /// Check whether no objects are interned.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of objects the interner can hold without growing its
+    /// internal storage.
+    fn capacity(&self) -> usize;
+
+    /// Reserve space for at least `additional` more interned objects.
+    fn reserve(&mut self, additional: usize);
+
+    /// Remove all interned objects.
+    ///
+    /// Outstanding references to interned objects stay valid, but are no
+    /// longer deduplicated against.
+    fn clear(&mut self);
+
+    /// Deallocate all interned objects that are no longer referenced and
+    /// shrink the internal storage to fit.
+    fn shrink_to_fit(&mut self);
+}
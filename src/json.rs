@@ -0,0 +1,208 @@
+use std::rc::Rc;
+use std::hash::{Hash, Hasher};
+use std::collections::BTreeMap;
+
+use crate::RcInterner;
+
+/// A JSON value whose strings and child values are reference-counted
+///
+/// Numbers are compared and hashed by their bit pattern, so that values can
+/// be interned. Object members are kept sorted by key.
+#[derive(Debug, Clone)]
+pub enum JsonValue {
+    /// The `null` value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A number.
+    Number(f64),
+    /// A string.
+    String(Rc<str>),
+    /// An array of values.
+    Array(Vec<Rc<JsonValue>>),
+    /// An object mapping keys to values.
+    Object(BTreeMap<Rc<str>, Rc<JsonValue>>),
+}
+
+impl PartialEq for JsonValue {
+    fn eq(&self, other: &JsonValue) -> bool {
+        match (self, other) {
+            (JsonValue::Null, JsonValue::Null) => true,
+            (JsonValue::Bool(a), JsonValue::Bool(b)) => a == b,
+            (JsonValue::Number(a), JsonValue::Number(b)) => {
+                a.to_bits() == b.to_bits()
+            }
+            (JsonValue::String(a), JsonValue::String(b)) => a == b,
+            (JsonValue::Array(a), JsonValue::Array(b)) => a == b,
+            (JsonValue::Object(a), JsonValue::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for JsonValue {}
+
+impl Hash for JsonValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            JsonValue::Null => {}
+            JsonValue::Bool(b) => b.hash(state),
+            JsonValue::Number(n) => n.to_bits().hash(state),
+            JsonValue::String(s) => s.hash(state),
+            JsonValue::Array(items) => items.hash(state),
+            JsonValue::Object(members) => members.hash(state),
+        }
+    }
+}
+
+/// An interner for JSON values sharing equal subtrees
+///
+/// Values are interned bottom-up: strings and object keys are interned in a
+/// string interner, child values are interned before their parents, and every
+/// node is interned in a node interner. Equal subtrees of different documents
+/// therefore share one allocation.
+///
+/// Like with `RcInterner`, interned values will be deallocated when there are
+/// no references to them any more and `shrink_to_fit()` is called on the
+/// interner.
+///
+/// This type is only available with the `json` feature.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// # use std::collections::BTreeMap;
+/// use refcount_interner::{JsonInterner, JsonValue};
+///
+/// fn document(name: &str) -> JsonValue {
+///     let tags = JsonValue::Array(vec![
+///         Rc::new(JsonValue::String(Rc::from("a"))),
+///         Rc::new(JsonValue::Number(1.0)),
+///     ]);
+///
+///     let mut members = BTreeMap::new();
+///     let name = JsonValue::String(Rc::from(name));
+///     members.insert(Rc::from("name"), Rc::new(name));
+///     members.insert(Rc::from("tags"), Rc::new(tags));
+///     JsonValue::Object(members)
+/// }
+///
+/// let mut interner = JsonInterner::new();
+///
+/// let x = interner.intern(document("x"));
+/// let y = interner.intern(document("y"));
+///
+/// match (x.as_ref(), y.as_ref()) {
+///     (JsonValue::Object(x), JsonValue::Object(y)) => {
+///         assert!(Rc::ptr_eq(&x["tags"], &y["tags"]));
+///         assert!(!Rc::ptr_eq(&x["name"], &y["name"]));
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct JsonInterner {
+    strings: RcInterner<str>,
+    nodes: RcInterner<JsonValue>,
+}
+
+impl JsonInterner {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{JsonInterner, JsonValue};
+    /// let mut interner = JsonInterner::new();
+    /// # let x = interner.intern(JsonValue::Null);
+    /// ```
+    pub fn new() -> JsonInterner {
+        Default::default()
+    }
+
+    /// Intern a JSON value and all of its children
+    ///
+    /// If an equal value has already been interned, a reference to the
+    /// already interned value will be returned. Otherwise, its children are
+    /// interned first, and the value is rebuilt from the interned children.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::{JsonInterner, JsonValue};
+    /// let mut interner = JsonInterner::new();
+    ///
+    /// let x = interner.intern(JsonValue::String(Rc::from("hello")));
+    /// let y = interner.intern(JsonValue::Array(vec![
+    ///     Rc::new(JsonValue::String(Rc::from("hello"))),
+    /// ]));
+    ///
+    /// match y.as_ref() {
+    ///     JsonValue::Array(items) => assert!(Rc::ptr_eq(&items[0], &x)),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn intern(&mut self, value: JsonValue) -> Rc<JsonValue> {
+        if let Some(interned) = self.nodes.try_intern(&value) {
+            return interned;
+        }
+
+        let value = match value {
+            JsonValue::String(s) => {
+                JsonValue::String(self.strings.intern_str(&s))
+            }
+            JsonValue::Array(items) => JsonValue::Array(
+                items.into_iter().map(|item| self.intern_rc(item)).collect()
+            ),
+            JsonValue::Object(members) => JsonValue::Object(
+                members.into_iter()
+                    .map(|(k, v)| {
+                        (self.strings.intern_str(&k), self.intern_rc(v))
+                    })
+                    .collect()
+            ),
+            value => value,
+        };
+
+        self.nodes.intern(value)
+    }
+
+    fn intern_rc(&mut self, value: Rc<JsonValue>) -> Rc<JsonValue> {
+        if self.nodes.is_canonical(&value) {
+            return value;
+        }
+
+        let value = Rc::try_unwrap(value).unwrap_or_else(|rc| (*rc).clone());
+        self.intern(value)
+    }
+
+    /// Deallocate all interned values and strings that are no longer
+    /// referenced and shrink the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::{JsonInterner, JsonValue};
+    /// let mut interner = JsonInterner::new();
+    ///
+    /// let x = interner.intern(JsonValue::Array(vec![
+    ///     Rc::new(JsonValue::Array(vec![Rc::new(JsonValue::Null)])),
+    /// ]));
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// let y = interner.intern(JsonValue::Null);
+    /// assert_eq!(Rc::strong_count(&y), 2);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        loop {
+            let generation = self.nodes.generation();
+            self.nodes.shrink_to_fit();
+            if self.nodes.generation() == generation {
+                break;
+            }
+        }
+
+        self.strings.shrink_to_fit();
+    }
+}
@@ -0,0 +1,148 @@
+use std::rc::Rc;
+use std::fmt;
+use std::hash::Hash;
+use std::collections::HashMap;
+
+/// An interner deduplicating objects by a key extracted from each object
+///
+/// The key is computed by a function passed to `new()`. Objects with equal
+/// keys are considered equal, even if they differ otherwise, and the first
+/// object interned with a given key is returned for all later objects with
+/// the same key.
+///
+/// Like with `RcInterner`, interned objects will be deallocated when there are
+/// no references to them any more and `shrink_to_fit()` is called on the
+/// interner.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::KeyedByInterner;
+///
+/// struct User {
+///     id: u32,
+///     name: &'static str,
+/// }
+///
+/// let mut interner = KeyedByInterner::new(|user: &User| user.id);
+///
+/// let x = interner.intern(User { id: 1, name: "alice" });
+/// let y = interner.intern(User { id: 1, name: "bob" });
+///
+/// assert!(Rc::ptr_eq(&x, &y));
+/// assert_eq!(y.name, "alice");
+/// ```
+pub struct KeyedByInterner<T, K, F> {
+    map: HashMap<K, Rc<T>>,
+    key_fn: F,
+}
+
+impl<T, K, F> KeyedByInterner<T, K, F>
+    where K: Hash + Eq, F: Fn(&T) -> K
+{
+    /// Create a new, empty interner using `key_fn` to extract the key of each
+    /// object.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::KeyedByInterner;
+    /// let mut interner = KeyedByInterner::new(|s: &String| s.len());
+    /// # let x = interner.intern(String::from("hello"));
+    /// ```
+    pub fn new(key_fn: F) -> KeyedByInterner<T, K, F> {
+        KeyedByInterner {
+            map: HashMap::new(),
+            key_fn,
+        }
+    }
+
+    /// Intern an owned object
+    ///
+    /// If an object with the same key has already been interned, the passed
+    /// object will be dropped, and a reference to the already interned object
+    /// will be returned.
+    ///
+    /// If no object with the same key has been interned yet, the passed object
+    /// will be moved into an `Rc<T>`, remembered for future calls to
+    /// `intern()`, and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::KeyedByInterner;
+    /// let mut interner = KeyedByInterner::new(|s: &String| s.len());
+    ///
+    /// let x = interner.intern(String::from("hello"));
+    /// let y = interner.intern(String::from("world"));
+    ///
+    /// assert_eq!(*y, "hello");
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        let key = (self.key_fn)(&t);
+        self.map.entry(key).or_insert_with(|| Rc::new(t)).clone()
+    }
+
+    /// Attempt to get a reference to an already interned object by its key.
+    ///
+    /// If no object with this key has been interned yet, `None` will be
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::KeyedByInterner;
+    /// let mut interner = KeyedByInterner::new(|s: &String| s.len());
+    ///
+    /// let x = interner.intern(String::from("hello"));
+    ///
+    /// assert_eq!(interner.get(&5), Some(x));
+    /// assert_eq!(interner.get(&3), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<Rc<T>> {
+        self.map.get(key).cloned()
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::KeyedByInterner;
+    /// let mut interner = KeyedByInterner::new(|s: &String| s.len());
+    ///
+    /// let x = interner.intern(String::from("hello"));
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.get(&5), None);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.map.retain(|_, value| Rc::strong_count(value) > 1);
+        self.map.shrink_to_fit();
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::KeyedByInterner;
+    /// let mut interner = KeyedByInterner::new(|s: &String| s.len());
+    ///
+    /// let x = interner.intern(String::from("hello"));
+    /// let y = interner.intern(String::from("world"));
+    /// interner.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        for (key, value) in &self.map {
+            assert!((self.key_fn)(value) == *key, "wrong key");
+        }
+    }
+}
+
+impl<T: fmt::Debug, K: fmt::Debug, F> fmt::Debug for KeyedByInterner<T, K, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("KeyedByInterner").field(&self.map).finish()
+    }
+}
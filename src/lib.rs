@@ -15,7 +15,8 @@
 //! dropped, unused interned objects are deallocated.
 //!
 //! The two kinds of interners provided by this crate are `RcInterner` and
-//! `ArcInterner`, returning `Rc<T>` and `Arc<T>` objects respectively.
+//! `ArcInterner`, returning `Rc<T>` and `Arc<T>` objects respectively. Both
+//! are aliases of `RefCountInterner`, which is generic over the pointer type.
 //!
 //! # Example
 //!
@@ -31,8 +32,95 @@
 //! assert!(Rc::ptr_eq(&hello, &interner.intern_str("hello")));
 //! ```
 
+mod interner;
+mod refcount;
+mod backend;
+pub mod selected;
+pub mod generational;
+mod refcount_interner;
 mod rc_interner;
 mod arc_interner;
+mod weak_interner;
+mod bloom;
+mod hit_rate;
+mod generation;
+mod checkpoint;
+mod metrics;
+mod diff;
+mod interned;
+mod handle;
+mod canon;
+mod internable;
+mod registry;
+mod cyclic_interner;
+mod recording;
+mod keyed_interner;
+mod count_min;
+mod recency;
+mod weighted_lru;
+mod counting;
+mod result_interner;
+mod ttl_interner;
+mod read_through;
+mod token_stream;
+mod token_str;
+mod partial;
+mod static_str;
+mod weak_key_map;
+mod symbol;
+mod compact_symbol;
+mod bucketed;
+mod deterministic;
+mod ffi;
+mod perfect;
+#[cfg(all(feature = "mmap", target_os = "linux", target_pointer_width = "64"))]
+mod mmap_interner;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "content-hash")]
+mod content;
 
-pub use rc_interner::RcInterner;
-pub use arc_interner::ArcInterner;
+pub use interner::Interner;
+pub use refcount::{RefCount, IntoLowHitRateCallback};
+pub use refcount_interner::{RefCountInterner, FrozenRefCountInterner};
+pub use backend::SetBackend;
+pub use rc_interner::{RcInterner, FrozenRcInterner};
+pub use arc_interner::{ArcInterner, FrozenArcInterner};
+pub use weak_interner::{WeakRcInterner, WeakArcInterner};
+pub use generation::GenTaggedHandle;
+pub use checkpoint::Checkpoint;
+pub use metrics::{InternerMetrics, InternerStats, WeakInternerStats};
+pub use diff::InternerDiff;
+pub use interned::Interned;
+pub use handle::{RefCountHandle, Handle, SyncHandle, InternedRef};
+pub use canon::CanonRule;
+pub use internable::Internable;
+pub use registry::InternerRegistry;
+pub use cyclic_interner::{CyclicInterner, CyclicNode};
+pub use recording::{RecordingRcInterner, Op};
+pub use keyed_interner::KeyedByInterner;
+pub use count_min::CountMinInterner;
+pub use recency::RecencyRcInterner;
+pub use weighted_lru::WeightedLruInterner;
+pub use counting::CountingInterner;
+pub use result_interner::ResultInterner;
+pub use ttl_interner::TtlRcInterner;
+pub use read_through::ReadThroughInterner;
+pub use token_stream::TokenStreamInterner;
+pub use token_str::TokenStrInterner;
+pub use partial::{PartialInterner, UncomparablePolicy};
+pub use static_str::{StaticStrInterner, StaticOrRc};
+pub use weak_key_map::WeakKeyMap;
+pub use symbol::{Symbol, SymbolInterner, SharedSymbolSpace};
+pub use compact_symbol::CompactSymbolInterner;
+pub use bucketed::BucketedInterner;
+pub use generational::GenerationalInterner;
+pub use deterministic::DeterministicInterner;
+pub use ffi::FfiStrInterner;
+pub use perfect::PerfectHashInterner;
+#[cfg(all(feature = "mmap", target_os = "linux", target_pointer_width = "64"))]
+pub use mmap_interner::MmapStrInterner;
+#[cfg(feature = "json")]
+pub use json::{JsonInterner, JsonValue};
+#[cfg(feature = "content-hash")]
+pub use content::ContentInterner;
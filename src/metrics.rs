@@ -0,0 +1,209 @@
+/// A snapshot of the metrics of an interner
+///
+/// Returned by `RcInterner::metrics()` and `ArcInterner::metrics()`. The
+/// metrics are read through getter methods, so that more metrics can be added
+/// later without breaking code using this struct.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+///
+/// let x = interner.intern(42);
+/// let y = interner.intern(42);
+/// drop(interner.intern(1337));
+///
+/// let metrics = interner.metrics();
+/// assert_eq!(metrics.len(), 2);
+/// assert!(metrics.capacity() >= 2);
+/// assert_eq!(metrics.collectible(), 1);
+/// assert_eq!(metrics.hits(), 1);
+/// assert_eq!(metrics.misses(), 2);
+/// ```
+///
+/// The struct can neither be constructed nor exhaustively destructured
+/// outside of this crate:
+///
+/// ```rust,compile_fail
+/// # use refcount_interner::{RcInterner, InternerMetrics};
+/// let interner = RcInterner::<u32>::new();
+///
+/// let InternerMetrics { len, capacity, collectible, hits, misses } =
+///     interner.metrics();
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InternerMetrics {
+    pub(crate) len: usize,
+    pub(crate) capacity: usize,
+    pub(crate) collectible: usize,
+    pub(crate) hits: u64,
+    pub(crate) misses: u64,
+}
+
+impl InternerMetrics {
+    /// Get the number of interned objects, including collectible ones.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether no objects are interned.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of objects the interner can hold without growing its
+    /// internal storage.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of interned objects that are no longer referenced
+    /// outside of the interner, and would be deallocated by
+    /// `shrink_to_fit()`.
+    pub fn collectible(&self) -> usize {
+        self.collectible
+    }
+
+    /// Get the number of intern calls that found an already interned object.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Get the number of intern calls that did not find an already interned
+    /// object.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Render the metrics in the Prometheus text exposition format, with
+    /// every metric name starting with `prefix`
+    pub(crate) fn to_prometheus(self, prefix: &str) -> String {
+        let gauges = [
+            ("len", self.len as u64),
+            ("capacity", self.capacity as u64),
+            ("collectible", self.collectible as u64),
+        ];
+        let counters = [
+            ("hits_total", self.hits),
+            ("misses_total", self.misses),
+        ];
+
+        let mut out = String::new();
+        let metrics = gauges.iter().map(|metric| (metric, "gauge"))
+            .chain(counters.iter().map(|metric| (metric, "counter")));
+        for ((name, value), kind) in metrics {
+            out.push_str(&format!("# TYPE {}_{} {}\n", prefix, name, kind));
+            out.push_str(&format!("{}_{} {}\n", prefix, name, value));
+        }
+        out
+    }
+}
+
+/// Counts of the live and dead objects of an interner
+///
+/// Returned by `RcInterner::stats()` and `ArcInterner::stats()`. An interned
+/// object is live if it is referenced outside of the interner, and dead if it
+/// is only referenced by the interner and would be deallocated by
+/// `shrink_to_fit()`.
+///
+/// Like `InternerMetrics`, the counts are read through getter methods.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+///
+/// let x = interner.intern(42);
+/// interner.intern(1337);
+/// interner.intern(0);
+///
+/// let stats = interner.stats();
+/// assert_eq!(stats.total(), 3);
+/// assert_eq!(stats.live(), 1);
+/// assert_eq!(stats.dead(), 2);
+///
+/// if stats.dead() * 2 > stats.total() {
+///     interner.shrink_to_fit();
+/// }
+/// assert_eq!(interner.stats().dead(), 0);
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InternerStats {
+    pub(crate) total: usize,
+    pub(crate) live: usize,
+}
+
+impl InternerStats {
+    /// Get the number of interned objects.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Get the number of interned objects that are referenced outside of the
+    /// interner.
+    pub fn live(&self) -> usize {
+        self.live
+    }
+
+    /// Get the number of interned objects that are only referenced by the
+    /// interner.
+    pub fn dead(&self) -> usize {
+        self.total - self.live
+    }
+}
+
+/// Counts of the lookups of a weak interner
+///
+/// Returned by `WeakRcInterner::stats()` and `WeakArcInterner::stats()`. A
+/// lookup is a hit if it finds a live interned object, and a miss otherwise.
+/// Entries of deallocated objects are counted as dead whenever a lookup or
+/// `prune()` removes them. Many dead entries found by lookups indicate that
+/// objects are frequently deallocated and interned again, and that calling
+/// `prune()` more often does not help.
+///
+/// Like `InternerMetrics`, the counts are read through getter methods.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::WeakRcInterner;
+///
+/// let mut interner = WeakRcInterner::new();
+///
+/// let x = interner.intern(42);
+/// interner.intern(42);
+/// interner.try_intern(&1337);
+///
+/// let stats = interner.stats();
+/// assert_eq!(stats.weak_hits(), 1);
+/// assert_eq!(stats.weak_misses(), 2);
+/// assert_eq!(stats.weak_dead(), 0);
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WeakInternerStats {
+    pub(crate) weak_hits: u64,
+    pub(crate) weak_misses: u64,
+    pub(crate) weak_dead: u64,
+}
+
+impl WeakInternerStats {
+    /// Get the number of lookups that found a live interned object.
+    pub fn weak_hits(&self) -> u64 {
+        self.weak_hits
+    }
+
+    /// Get the number of lookups that did not find a live interned object.
+    pub fn weak_misses(&self) -> u64 {
+        self.weak_misses
+    }
+
+    /// Get the number of entries of deallocated objects that have been
+    /// removed.
+    pub fn weak_dead(&self) -> u64 {
+        self.weak_dead
+    }
+}
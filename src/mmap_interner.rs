@@ -0,0 +1,355 @@
+use std::io;
+use std::ptr;
+use std::str;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::hash::BuildHasher;
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::os::raw::{c_int, c_void};
+use std::os::unix::io::AsRawFd;
+
+extern "C" {
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: i64,
+    ) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+const PROT_READ: c_int = 1;
+const PROT_WRITE: c_int = 2;
+const MAP_SHARED: c_int = 1;
+
+const HEADER_LEN: u32 = 4;
+const LEN_PREFIX: u32 = 4;
+
+/// A string interner storing interned strings in a memory-mapped file
+///
+/// The file consists of a 4-byte header containing the number of bytes in use,
+/// followed by the interned strings, each prefixed with its length in bytes.
+/// All integers are stored in little-endian byte order. Interned strings are
+/// identified by their offset into the file, which stays valid for as long as
+/// the file exists and can be shared with other processes.
+///
+/// The file has a fixed capacity that is set when it is created. Interning
+/// fails with an error once the capacity is exhausted.
+///
+/// Only a single process may intern strings into the file at any time.
+/// Other processes can open the file to resolve offsets with `open()`, which
+/// rebuilds the index of interned strings from the file contents.
+///
+/// This interner is only available with the `mmap` feature on 64-bit Linux.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::MmapStrInterner;
+///
+/// let path = std::env::temp_dir().join("interner-struct");
+/// let mut interner = unsafe { MmapStrInterner::create(&path, 4096)? };
+///
+/// let x = interner.intern("hello")?;
+/// let y = interner.intern("world")?;
+///
+/// assert_eq!(x, interner.intern("hello")?);
+/// assert_eq!(interner.resolve(x), Some("hello"));
+/// assert_eq!(interner.resolve(y), Some("world"));
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct MmapStrInterner {
+    data: *mut u8,
+    capacity: u32,
+    used: u32,
+    index: HashMap<u64, Vec<u32>>,
+    hasher: RandomState,
+    _file: File,
+}
+
+impl MmapStrInterner {
+    /// Create a new file at `path` with room for `capacity` bytes and map it
+    /// into memory.
+    ///
+    /// If the file already exists, it is truncated.
+    ///
+    /// # Safety
+    /// The file must not be modified by other processes or other mappings
+    /// while the interner is alive.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::MmapStrInterner;
+    /// let path = std::env::temp_dir().join("interner-create");
+    /// let mut interner = unsafe { MmapStrInterner::create(&path, 4096)? };
+    /// # let x = interner.intern("hello")?;
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub unsafe fn create(path: impl AsRef<Path>, capacity: u32)
+        -> io::Result<MmapStrInterner>
+    {
+        if capacity < HEADER_LEN {
+            let msg = "capacity too small";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(capacity as u64)?;
+
+        let mut interner = MmapStrInterner::map(file, capacity)?;
+        interner.set_used(HEADER_LEN);
+        Ok(interner)
+    }
+
+    /// Open an existing file at `path`, map it into memory and rebuild the
+    /// index of interned strings from its contents.
+    ///
+    /// # Safety
+    /// The file must not be modified by other processes or other mappings
+    /// while the interner is alive.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::MmapStrInterner;
+    /// let path = std::env::temp_dir().join("interner-open");
+    ///
+    /// let mut interner = unsafe { MmapStrInterner::create(&path, 4096)? };
+    /// let x = interner.intern("hello")?;
+    /// drop(interner);
+    ///
+    /// let mut interner = unsafe { MmapStrInterner::open(&path)? };
+    /// assert_eq!(interner.resolve(x), Some("hello"));
+    /// assert_eq!(interner.intern("hello")?, x);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<MmapStrInterner> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len();
+        if len < HEADER_LEN as u64 || len > u32::MAX as u64 {
+            return Err(invalid_data("invalid file size"));
+        }
+
+        let mut interner = MmapStrInterner::map(file, len as u32)?;
+        let used = interner.read_u32(0);
+        if used < HEADER_LEN || used > interner.capacity {
+            return Err(invalid_data("invalid header"));
+        }
+
+        interner.used = used;
+
+        let mut offset = HEADER_LEN;
+        while offset < used {
+            let s = interner.read_entry(offset)
+                .ok_or_else(|| invalid_data("invalid entry"))?;
+            let hash = interner.hash(s);
+            let next = offset + LEN_PREFIX + s.len() as u32;
+            interner.index.entry(hash).or_default().push(offset);
+            offset = next;
+        }
+
+        Ok(interner)
+    }
+
+    unsafe fn map(file: File, capacity: u32) -> io::Result<MmapStrInterner> {
+        let data = mmap(
+            ptr::null_mut(),
+            capacity as usize,
+            PROT_READ | PROT_WRITE,
+            MAP_SHARED,
+            file.as_raw_fd(),
+            0,
+        );
+
+        if data as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(MmapStrInterner {
+            data: data as *mut u8,
+            capacity,
+            used: HEADER_LEN,
+            index: HashMap::new(),
+            hasher: RandomState::new(),
+            _file: file,
+        })
+    }
+
+    /// Intern a string slice, returning its offset in the file
+    ///
+    /// If the string slice has already been interned, the offset of the
+    /// already interned string slice will be returned.
+    ///
+    /// If the string slice has not yet been interned, it is appended to the
+    /// file. If there is not enough space left in the file, an error will be
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::MmapStrInterner;
+    /// let path = std::env::temp_dir().join("interner-intern");
+    /// let mut interner = unsafe { MmapStrInterner::create(&path, 16)? };
+    ///
+    /// let x = interner.intern("hello")?;
+    /// assert_eq!(interner.intern("hello")?, x);
+    /// assert!(interner.intern("world").is_err());
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn intern(&mut self, s: &str) -> io::Result<u32> {
+        if let Some(offset) = self.get(s) {
+            return Ok(offset);
+        }
+
+        let offset = self.used;
+        let next = (s.len() as u64) + (offset + LEN_PREFIX) as u64;
+        if next > self.capacity as u64 {
+            let msg = "interner is full";
+            return Err(io::Error::new(io::ErrorKind::WriteZero, msg));
+        }
+
+        unsafe {
+            self.write_u32(offset, s.len() as u32);
+            let dst = self.data.add((offset + LEN_PREFIX) as usize);
+            ptr::copy_nonoverlapping(s.as_ptr(), dst, s.len());
+        }
+
+        self.set_used(next as u32);
+
+        let hash = self.hash(s);
+        self.index.entry(hash).or_default().push(offset);
+        Ok(offset)
+    }
+
+    /// Attempt to get the offset of an already interned string slice.
+    ///
+    /// If the string slice has not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::MmapStrInterner;
+    /// let path = std::env::temp_dir().join("interner-get");
+    /// let mut interner = unsafe { MmapStrInterner::create(&path, 4096)? };
+    ///
+    /// let x = interner.intern("hello")?;
+    /// assert_eq!(interner.get("hello"), Some(x));
+    /// assert_eq!(interner.get("world"), None);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn get(&self, s: &str) -> Option<u32> {
+        let offsets = self.index.get(&self.hash(s))?;
+        offsets.iter()
+            .copied()
+            .find(|&offset| self.read_entry(offset) == Some(s))
+    }
+
+    /// Get the interned string slice at the given offset
+    ///
+    /// If the offset is not the offset of an interned string slice, e.g. if it
+    /// points into the middle of one, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::MmapStrInterner;
+    /// let path = std::env::temp_dir().join("interner-resolve");
+    /// let mut interner = unsafe { MmapStrInterner::create(&path, 4096)? };
+    ///
+    /// let x = interner.intern("hello")?;
+    /// assert_eq!(interner.resolve(x), Some("hello"));
+    /// assert_eq!(interner.resolve(x + 1), None);
+    /// assert_eq!(interner.resolve(4000), None);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn resolve(&self, offset: u32) -> Option<&str> {
+        let s = self.read_entry(offset)?;
+        let offsets = self.index.get(&self.hash(s))?;
+        if offsets.contains(&offset) {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    /// Read the string slice stored at the given offset, without checking
+    /// that an entry starts there
+    fn read_entry(&self, offset: u32) -> Option<&str> {
+        if offset < HEADER_LEN || offset > self.used.checked_sub(LEN_PREFIX)? {
+            return None;
+        }
+
+        let len = unsafe { self.read_u32(offset) };
+        let start = offset + LEN_PREFIX;
+        if len > self.used - start {
+            return None;
+        }
+
+        let bytes = unsafe {
+            let data = self.data.add(start as usize);
+            std::slice::from_raw_parts(data, len as usize)
+        };
+        str::from_utf8(bytes).ok()
+    }
+
+    /// Get the number of bytes of the file that are in use.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::MmapStrInterner;
+    /// let path = std::env::temp_dir().join("interner-used");
+    /// let mut interner = unsafe { MmapStrInterner::create(&path, 4096)? };
+    ///
+    /// let x = interner.intern("hello")?;
+    /// assert_eq!(interner.used(), 4 + 4 + 5);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn used(&self) -> u32 {
+        self.used
+    }
+
+    fn hash(&self, s: &str) -> u64 {
+        self.hasher.hash_one(s)
+    }
+
+    fn set_used(&mut self, used: u32) {
+        unsafe { self.write_u32(0, used) };
+        self.used = used;
+    }
+
+    unsafe fn read_u32(&self, offset: u32) -> u32 {
+        let mut bytes = [0; 4];
+        let src = self.data.add(offset as usize);
+        ptr::copy_nonoverlapping(src, bytes.as_mut_ptr(), 4);
+        u32::from_le_bytes(bytes)
+    }
+
+    unsafe fn write_u32(&mut self, offset: u32, value: u32) {
+        let bytes = value.to_le_bytes();
+        let dst = self.data.add(offset as usize);
+        ptr::copy_nonoverlapping(bytes.as_ptr(), dst, 4);
+    }
+}
+
+impl Drop for MmapStrInterner {
+    fn drop(&mut self) {
+        unsafe {
+            munmap(self.data as *mut c_void, self.capacity as usize);
+        }
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
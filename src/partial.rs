@@ -0,0 +1,238 @@
+use std::rc::Rc;
+use std::cmp::Ordering;
+
+/// How a `PartialInterner` handles values that are not equal to themselves
+///
+/// Such values, like floating point NaNs, cannot be found again by
+/// comparison, so they need a separate policy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UncomparablePolicy {
+    /// Refuse to intern uncomparable values, returning them as an error.
+    #[default]
+    Reject,
+    /// Intern all uncomparable values as one shared value, the first one
+    /// interned.
+    Canonicalize,
+    /// Intern every uncomparable value separately, never sharing them.
+    AllowDistinct,
+}
+
+/// An interner returning reference-counted pointers to the interned data, for
+/// types that are only partially ordered
+///
+/// Values that are equal to themselves are stored in a sorted vector and
+/// deduplicated like with `RcInterner`. These values must be totally ordered
+/// among themselves, which holds for floating point numbers except NaN. Values
+/// that are not equal to themselves are handled according to an
+/// `UncomparablePolicy`.
+///
+/// Interned objects will be deallocated when there are no references to them
+/// any more and `shrink_to_fit()` is called on the interner.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::{PartialInterner, UncomparablePolicy};
+///
+/// let mut interner = PartialInterner::new(UncomparablePolicy::Reject);
+///
+/// let x = interner.intern(1.5).unwrap();
+/// let y = interner.intern(1.5).unwrap();
+///
+/// assert!(Rc::ptr_eq(&x, &y));
+/// assert!(interner.intern(f64::NAN).is_err());
+/// ```
+#[derive(Debug)]
+pub struct PartialInterner<T> {
+    values: Vec<Rc<T>>,
+    uncomparable: Option<Rc<T>>,
+    policy: UncomparablePolicy,
+}
+
+impl<T> Default for PartialInterner<T> {
+    fn default() -> PartialInterner<T> {
+        PartialInterner::new(UncomparablePolicy::default())
+    }
+}
+
+impl<T> PartialInterner<T> {
+    /// Create a new, empty interner with the given policy for uncomparable
+    /// values.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{PartialInterner, UncomparablePolicy};
+    /// let mut interner = PartialInterner::new(UncomparablePolicy::Reject);
+    /// # let x = interner.intern(1.5);
+    /// ```
+    pub fn new(policy: UncomparablePolicy) -> PartialInterner<T> {
+        PartialInterner {
+            values: Vec::new(),
+            uncomparable: None,
+            policy,
+        }
+    }
+
+    /// Get the policy for uncomparable values.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{PartialInterner, UncomparablePolicy};
+    /// let interner = PartialInterner::<f64>::default();
+    /// assert_eq!(interner.policy(), UncomparablePolicy::Reject);
+    /// ```
+    pub fn policy(&self) -> UncomparablePolicy {
+        self.policy
+    }
+}
+
+impl<T: PartialOrd> PartialInterner<T> {
+    /// Intern an owned object
+    ///
+    /// If the object is equal to itself, it is interned like with
+    /// `RcInterner::intern()`.
+    ///
+    /// Otherwise it is handled according to the policy of the interner:
+    /// with `Reject`, the object is returned as an error; with
+    /// `Canonicalize`, a reference to the first interned uncomparable object
+    /// is returned; and with `AllowDistinct`, the object is moved into a new
+    /// `Rc<T>` that is not remembered by the interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::{PartialInterner, UncomparablePolicy};
+    /// let mut reject = PartialInterner::new(UncomparablePolicy::Reject);
+    /// assert!(reject.intern(f64::NAN).unwrap_err().is_nan());
+    ///
+    /// let mut canonicalize =
+    ///     PartialInterner::new(UncomparablePolicy::Canonicalize);
+    /// let x = canonicalize.intern(f64::NAN).unwrap();
+    /// let y = canonicalize.intern(-f64::NAN).unwrap();
+    /// assert!(Rc::ptr_eq(&x, &y));
+    ///
+    /// let mut distinct =
+    ///     PartialInterner::new(UncomparablePolicy::AllowDistinct);
+    /// let x = distinct.intern(f64::NAN).unwrap();
+    /// let y = distinct.intern(f64::NAN).unwrap();
+    /// assert!(!Rc::ptr_eq(&x, &y));
+    /// assert_eq!(distinct.len(), 0);
+    /// ```
+    pub fn intern(&mut self, t: T) -> Result<Rc<T>, T> {
+        if is_uncomparable(&t) {
+            return match self.policy {
+                UncomparablePolicy::Reject => Err(t),
+                UncomparablePolicy::Canonicalize => {
+                    Ok(self.uncomparable.get_or_insert_with(|| Rc::new(t))
+                        .clone())
+                }
+                UncomparablePolicy::AllowDistinct => Ok(Rc::new(t)),
+            };
+        }
+
+        match self.search(&t) {
+            Ok(index) => Ok(self.values[index].clone()),
+            Err(index) => {
+                let value = Rc::new(t);
+                self.values.insert(index, value.clone());
+                Ok(value)
+            }
+        }
+    }
+
+    /// Attempt to get a reference to an already interned object.
+    ///
+    /// If the object has not yet been interned, `None` will be returned.
+    /// Uncomparable objects are only found with the `Canonicalize` policy.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{PartialInterner, UncomparablePolicy};
+    /// let mut interner =
+    ///     PartialInterner::new(UncomparablePolicy::Canonicalize);
+    ///
+    /// let x = interner.intern(1.5).unwrap();
+    /// let y = interner.intern(f64::NAN).unwrap();
+    ///
+    /// assert_eq!(interner.try_intern(&1.5), Some(x));
+    /// assert_eq!(interner.try_intern(&2.5), None);
+    /// assert!(interner.try_intern(&f64::NAN).is_some());
+    /// ```
+    pub fn try_intern(&self, t: &T) -> Option<Rc<T>> {
+        if is_uncomparable(t) {
+            return match self.policy {
+                UncomparablePolicy::Canonicalize => self.uncomparable.clone(),
+                _ => None,
+            };
+        }
+
+        self.search(t).ok().map(|index| self.values[index].clone())
+    }
+
+    /// Get the number of interned objects.
+    ///
+    /// Uncomparable objects are only counted with the `Canonicalize` policy.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{PartialInterner, UncomparablePolicy};
+    /// let mut interner =
+    ///     PartialInterner::new(UncomparablePolicy::Canonicalize);
+    ///
+    /// interner.intern(1.5);
+    /// interner.intern(1.5);
+    /// interner.intern(f64::NAN);
+    /// interner.intern(f64::NAN);
+    /// assert_eq!(interner.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.values.len() + self.uncomparable.iter().count()
+    }
+
+    /// Check whether no objects are interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::PartialInterner;
+    /// let interner = PartialInterner::<f64>::default();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{PartialInterner, UncomparablePolicy};
+    /// let mut interner =
+    ///     PartialInterner::new(UncomparablePolicy::Canonicalize);
+    ///
+    /// let x = interner.intern(1.5).unwrap();
+    /// drop(interner.intern(f64::NAN));
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.values.retain(|value| Rc::strong_count(value) > 1);
+        self.values.shrink_to_fit();
+
+        if self.uncomparable.as_ref().map(Rc::strong_count) == Some(1) {
+            self.uncomparable = None;
+        }
+    }
+
+    fn search(&self, t: &T) -> Result<usize, usize> {
+        self.values.binary_search_by(|value| {
+            value.as_ref().partial_cmp(t).unwrap_or(Ordering::Less)
+        })
+    }
+}
+
+/// Check whether a value is not equal to itself
+fn is_uncomparable<T: PartialOrd>(t: &T) -> bool {
+    t.partial_cmp(t) != Some(Ordering::Equal)
+}
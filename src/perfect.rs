@@ -0,0 +1,203 @@
+use std::ops::Deref;
+use std::cmp::Reverse;
+use std::hash::{Hash, BuildHasher};
+use std::collections::hash_map::RandomState;
+
+/// Number of displacements tried for a bucket before starting over with a
+/// new hasher
+const MAX_DISPLACEMENT: u32 = 1 << 16;
+
+/// A read-only interner using a perfect hash function for lookups
+///
+/// Created by `RcInterner::finalize()` or `ArcInterner::finalize()`. The
+/// perfect hash function is constructed with the
+/// [CHD](http://cmph.sourceforge.net/papers/esa09.pdf) algorithm: the
+/// interned objects are split into small buckets by hash, and each bucket is
+/// assigned a displacement that moves all of its objects to free slots of the
+/// table. Looking up an object therefore hashes it once and compares it with
+/// exactly one slot, and never allocates.
+///
+/// Objects can no longer be interned once the interner has been finalized.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+/// let words: Vec<_> = ["a", "b", "c"].iter()
+///     .map(|s| interner.intern_str(s))
+///     .collect();
+///
+/// let finalized = interner.finalize();
+/// for word in &words {
+///     assert_eq!(finalized.get(word), Some(word));
+/// }
+/// assert_eq!(finalized.get("d"), None);
+/// ```
+#[derive(Debug)]
+pub struct PerfectHashInterner<H> {
+    slots: Vec<Option<H>>,
+    displacements: Vec<u32>,
+    hasher: RandomState,
+}
+
+impl<H: Deref> PerfectHashInterner<H>
+where
+    H::Target: Hash + Eq,
+{
+    pub(crate) fn new(handles: Vec<H>) -> PerfectHashInterner<H> {
+        let buckets = handles.len() / 3 + 1;
+        let (hasher, displacements) = loop {
+            let hasher = RandomState::new();
+            let hashes = handles.iter().map(|h| hasher.hash_one(h.deref()));
+            if let Some(displacements) = displace(hashes, buckets) {
+                break (hasher, displacements);
+            }
+        };
+
+        let mut slots = Vec::new();
+        slots.resize_with(buckets * 4, || None);
+
+        let mut interner = PerfectHashInterner {
+            slots,
+            displacements,
+            hasher,
+        };
+        for handle in handles {
+            let slot = interner.slot(handle.deref());
+            interner.slots[slot] = Some(handle);
+        }
+        interner
+    }
+
+    fn slot(&self, t: &H::Target) -> usize {
+        let hash = self.hasher.hash_one(t);
+        let bucket = bucket_of(hash, self.displacements.len());
+        slot_of(hash, self.displacements[bucket], self.slots.len())
+    }
+
+    /// Get a reference to an interned object.
+    ///
+    /// If the object has not been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// for i in 0..1000 {
+    ///     interner.intern(i);
+    /// }
+    ///
+    /// let finalized = interner.finalize();
+    /// for i in 0..1000 {
+    ///     assert_eq!(finalized.get(&i), Some(&Rc::new(i)));
+    /// }
+    /// for i in 1000..2000 {
+    ///     assert_eq!(finalized.get(&i), None);
+    /// }
+    /// ```
+    pub fn get(&self, t: &H::Target) -> Option<&H> {
+        self.slots[self.slot(t)].as_ref()
+            .filter(|&value| value.deref() == t)
+    }
+
+    /// Iterate over all interned objects in arbitrary order.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let x = interner.intern(42);
+    ///
+    /// let finalized = interner.finalize();
+    /// assert_eq!(finalized.iter().collect::<Vec<_>>(), vec![&x]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &H> + '_ {
+        self.slots.iter().flatten()
+    }
+
+    /// Get the number of interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// interner.intern(42);
+    /// interner.intern(1337);
+    ///
+    /// assert_eq!(interner.finalize().len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Check whether no objects have been interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let interner = RcInterner::<u32>::new();
+    /// assert!(interner.finalize().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+/// Find a displacement for each bucket that moves all of its hashes to free
+/// slots of a table with four slots per bucket
+///
+/// Returns `None` if no displacement was found for some bucket.
+fn displace<I>(hashes: I, buckets: usize) -> Option<Vec<u32>>
+where
+    I: Iterator<Item = u64>,
+{
+    let mut members = vec![Vec::new(); buckets];
+    for hash in hashes {
+        members[bucket_of(hash, buckets)].push(hash);
+    }
+
+    let mut order: Vec<usize> = (0..buckets).collect();
+    order.sort_unstable_by_key(|&bucket| Reverse(members[bucket].len()));
+
+    let size = buckets * 4;
+    let mut taken = vec![false; size];
+    let mut displacements = vec![0; buckets];
+    let mut chosen = Vec::new();
+    for bucket in order {
+        let hashes = &members[bucket];
+        if hashes.is_empty() {
+            break;
+        }
+
+        displacements[bucket] = (0..MAX_DISPLACEMENT).find(|&displacement| {
+            chosen.clear();
+            hashes.iter().all(|&hash| {
+                let slot = slot_of(hash, displacement, size);
+                let free = !taken[slot] && !chosen.contains(&slot);
+                chosen.push(slot);
+                free
+            })
+        })?;
+
+        for &slot in &chosen {
+            taken[slot] = true;
+        }
+    }
+
+    Some(displacements)
+}
+
+fn bucket_of(hash: u64, buckets: usize) -> usize {
+    (hash % buckets as u64) as usize
+}
+
+fn slot_of(hash: u64, displacement: u32, size: usize) -> usize {
+    let mut x = hash.rotate_left(32) ^ u64::from(displacement);
+    x = x.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    x ^= x >> 29;
+    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x ^= x >> 32;
+    (x % size as u64) as usize
+}
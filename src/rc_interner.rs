@@ -1,7 +1,12 @@
 use std::rc::Rc;
+use std::sync::Arc;
 use std::hash::Hash;
+use std::iter::FromIterator;
 use std::collections::HashSet;
 
+use crate::backend::SetBackend;
+use crate::refcount_interner::{RefCountInterner, FrozenRefCountInterner};
+
 /// An interner returning reference-counted pointers to the interned data
 ///
 /// Interned objects will be deallocated when there are no references to them
@@ -21,284 +26,207 @@ use std::collections::HashSet;
 /// assert_ne!(x, y);
 /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
 /// ```
-#[derive(Debug)]
-pub struct RcInterner<T: ?Sized>(HashSet<Rc<T>>);
-
-impl<T: ?Sized> Default for RcInterner<T> {
-    fn default() -> RcInterner<T> {
-        RcInterner(HashSet::new())
-    }
-}
+///
+/// The interned objects are stored in a `HashSet` by default. A custom hasher
+/// can be used with `with_hasher()`. Other sets can be used by implementing
+/// `SetBackend` for them, see `with_backend()`.
+///
+/// This is `RefCountInterner` returning `Rc<T>` handles, see there for all
+/// methods that are available on both `RcInterner` and `ArcInterner`.
+pub type RcInterner<T, B = HashSet<Rc<T>>> = RefCountInterner<Rc<T>, T, B>;
 
-impl<T: ?Sized + Hash + Eq> RcInterner<T> {
-    /// Create a new, empty interner.
-    ///
-    /// # Example
-    /// ```rust
-    /// # use refcount_interner::RcInterner;
-    /// let mut interner = RcInterner::new();
-    /// # let x = interner.intern(42);
-    /// ```
-    pub fn new() -> RcInterner<T> {
-        Default::default()
-    }
+/// A read-only interner produced by `RcInterner::freeze()`
+///
+/// A frozen interner only supports looking up already interned objects by
+/// reference, so no lookup allocates or modifies reference counts. Use
+/// `thaw()` to convert it back into an `RcInterner`.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+/// let x = interner.intern(42);
+///
+/// let frozen = interner.freeze();
+/// assert!(Rc::ptr_eq(&x, frozen.get(&42).unwrap()));
+/// assert_eq!(frozen.get(&1337), None);
+/// ```
+pub type FrozenRcInterner<T, B = HashSet<Rc<T>>> =
+    FrozenRefCountInterner<Rc<T>, T, B>;
 
-    /// Attempt to get a reference to an already interned object.
-    ///
-    /// If the object has already been interned, an option containing a
-    /// reference to the already interned object will be returned.
+impl<T: ?Sized + Hash + Eq, B: SetBackend<Rc<T>>> RcInterner<T, B> {
+    /// Intern an already allocated `Rc<T>`
     ///
-    /// If the object has not yet been interned, `None` will be returned.
+    /// See `intern_shared()`.
     ///
     /// # Example
     /// ```rust
     /// # use std::rc::Rc;
-    /// # use refcount_interner::RcInterner;
-    /// let mut interner = RcInterner::new();
-    ///
-    /// let x = interner.intern(42);
-    /// assert_eq!(interner.try_intern(&42), Some(Rc::new(42)));
-    /// assert_eq!(interner.try_intern(&1337), None);
+    /// # use refcount_interner::{RcInterner, CanonRule};
+    /// let a = Rc::new(42);
+    /// let b = Rc::new(42);
+    /// let lowest = if Rc::as_ptr(&a) < Rc::as_ptr(&b) { &a } else { &b };
+    ///
+    /// let mut forward = RcInterner::new();
+    /// forward.set_canonicalization(CanonRule::LowestAddress);
+    /// forward.intern_rc(a.clone());
+    /// forward.intern_rc(b.clone());
+    ///
+    /// let mut backward = RcInterner::new();
+    /// backward.set_canonicalization(CanonRule::LowestAddress);
+    /// backward.intern_rc(b.clone());
+    /// backward.intern_rc(a.clone());
+    ///
+    /// assert!(Rc::ptr_eq(&forward.intern(42), lowest));
+    /// assert!(Rc::ptr_eq(&backward.intern(42), lowest));
+    ///
+    /// let mut first_seen = RcInterner::new();
+    /// first_seen.intern_rc(b.clone());
+    /// assert!(Rc::ptr_eq(&first_seen.intern_rc(a.clone()), &b));
     /// ```
-    pub fn try_intern(&self, t: &T) -> Option<Rc<T>> {
-        self.0.get(t).cloned()
+    pub fn intern_rc(&mut self, value: Rc<T>) -> Rc<T> {
+        self.intern_shared(value)
     }
+}
 
-    /// Intern a boxed object
-    ///
-    /// This method must be used to intern unsized types, since unsized types
-    /// cannot be passed to `intern()`. The two most common unsized types,
-    /// `&[T]` and `&str` can be interned with `intern_slice()` and
-    /// `intern_str()` as well.
-    ///
-    /// If the object has already been interned, the passed object will be
-    /// dropped and deallocated, and a reference to the already interned object
-    /// will be returned.
-    ///
-    /// If the object has not yet been interned, the passed object will be moved
-    /// into an `Rc<T>`, remembered for future calls to `intern()`, and
-    /// returned.
-    ///
-    /// # Example
-    /// ```rust
-    /// # use refcount_interner::RcInterner;
-    /// let mut interner = RcInterner::new();
+impl<T, B> RcInterner<T, B>
+    where T: Sized + Hash + Eq + Clone, B: SetBackend<Rc<T>>
+{
+    /// Copy an interned object into a new `Arc<T>`, for sending it to another
+    /// thread
     ///
-    /// let x = Box::new(42);
-    /// let y = interner.intern_boxed(x);
-    ///
-    /// assert_eq!(*y, 42);
-    /// ```
-    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
-        if let Some(value) = self.0.get(t.as_ref()) {
-            value.clone()
-        } else {
-            let value: Rc<T> = Rc::from(t);
-            self.0.insert(value.clone());
-            value
-        }
-    }
-
-    /// Deallocate all interned objects that are no longer referenced and shrink
-    /// the internal storage to fit.
+    /// The returned `Arc<T>` is an independent copy: it is not remembered by
+    /// this interner and not deduplicated against any `ArcInterner`.
     ///
     /// # Example
     /// ```rust
-    /// # use std::rc::Rc;
+    /// # use std::thread;
     /// # use refcount_interner::RcInterner;
     /// let mut interner = RcInterner::new();
     ///
     /// let x = interner.intern(42);
-    /// let y = interner.intern(1337);
-    /// let z = y.clone();
-    ///
-    /// drop(x);
-    /// drop(y);
+    /// let y = interner.to_arc(&x);
     ///
-    /// interner.shrink_to_fit();
-    /// assert_eq!(interner.try_intern(&42), None);
-    /// assert_eq!(interner.try_intern(&1337), Some(Rc::new(1337)));
+    /// let z = thread::spawn(move || *y).join().unwrap();
+    /// assert_eq!(z, 42);
     /// ```
-    pub fn shrink_to_fit(&mut self) {
-        self.0.retain(|value| Rc::strong_count(value) > 1);
-        self.0.shrink_to_fit();
+    pub fn to_arc(&self, handle: &Rc<T>) -> Arc<T> {
+        Arc::new(T::clone(handle))
     }
 }
 
-impl<T: Sized + Hash + Eq> RcInterner<T> {
-    /// Intern an owned object
+impl<T, B> RcInterner<[T], B>
+    where T: Sized + Hash + Eq + Clone, B: SetBackend<Rc<[T]>>
+{
+    /// Copy an interned slice into a new `Arc<[T]>`, for sending it to another
+    /// thread
     ///
-    /// If the object has already been interned, the passed object will be
-    /// dropped, and a reference to the already interned object will be
-    /// returned.
-    ///
-    /// If the object has not yet been interned, the passed object will be moved
-    /// into an `Rc<T>`, remembered for future calls to `intern()`, and
-    /// returned.
+    /// The returned `Arc<[T]>` is an independent copy: it is not remembered by
+    /// this interner and not deduplicated against any `ArcInterner`.
     ///
     /// # Example
     /// ```rust
-    /// # use std::rc::Rc;
+    /// # use std::thread;
     /// # use refcount_interner::RcInterner;
     /// let mut interner = RcInterner::new();
     ///
-    /// let x = interner.intern(42);
-    /// let y = interner.intern(1337);
+    /// let x = interner.intern_slice(&[1, 2, 3]);
+    /// let y = interner.to_arc(&x);
     ///
-    /// assert_eq!(*x, 42);
-    /// assert_ne!(x, y);
-    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// let z = thread::spawn(move || y.iter().sum::<i32>()).join().unwrap();
+    /// assert_eq!(z, 6);
     /// ```
-    pub fn intern(&mut self, t: T) -> Rc<T> {
-        if let Some(value) = self.0.get(&t) {
-            value.clone()
-        } else {
-            let value = Rc::new(t);
-            self.0.insert(value.clone());
-            value
-        }
+    pub fn to_arc(&self, handle: &Rc<[T]>) -> Arc<[T]> {
+        Arc::from(handle.as_ref())
     }
 }
 
-impl<T: Sized + Hash + Eq + Clone> RcInterner<T> {
-    /// Intern a borrowed object, cloning if it has not yet been interned
+impl<B: SetBackend<Rc<str>>> RcInterner<str, B> {
+    /// Copy an interned string slice into a new `Arc<str>`, for sending it to
+    /// another thread
     ///
-    /// If the object has already been interned, a reference to the already
-    /// interned object will be returned.
-    ///
-    /// If the object has not yet been interned, the passed object will be moved
-    /// into an `Rc<T>`, remembered for future calls to `intern()`, and
-    /// returned.
+    /// The returned `Arc<str>` is an independent copy: it is not remembered by
+    /// this interner and not deduplicated against any `ArcInterner`.
     ///
     /// # Example
     /// ```rust
+    /// # use std::sync::Arc;
+    /// # use std::thread;
     /// # use refcount_interner::RcInterner;
     /// let mut interner = RcInterner::new();
     ///
-    /// let x = 42;
-    /// let y = interner.intern_cloned(&x);
+    /// let x = interner.intern_str("hello");
+    /// let y: Arc<str> = interner.to_arc(&x);
     ///
-    /// assert_eq!(x, *y);
+    /// let z = thread::spawn(move || y.len()).join().unwrap();
+    /// assert_eq!(z, 5);
     /// ```
-    pub fn intern_cloned(&mut self, t: &T) -> Rc<T> {
-        if let Some(value) = self.0.get(t) {
-            value.clone()
-        } else {
-            let value = Rc::new(t.clone());
-            self.0.insert(value.clone());
-            value
-        }
+    pub fn to_arc(&self, handle: &Rc<str>) -> Arc<str> {
+        Arc::from(handle.as_ref())
     }
 }
 
-impl<T: Sized + Hash + Eq + Clone> RcInterner<[T]> {
-    /// Intern a slice object
-    ///
-    /// This method can be used to intern slices without boxing them.
-    ///
-    /// If the slice has already been interned, a reference to the already
-    /// interned slice will be returned.
-    ///
-    /// If the slice has not yet been interned, the passed object will be
-    /// cloned into an `Rc<[T]>`, remembered for future calls to `intern()`, and
+impl<T, B> RcInterner<Rc<T>, B>
+    where T: ?Sized + Hash + Eq, B: SetBackend<Rc<Rc<T>>>
+{
+    /// Intern a reference-counted pointer
+    ///
+    /// Since `Rc<T>` is hashed and compared by value, two pointers to equal
+    /// objects are interned as the same `Rc<Rc<T>>`, even if they point to
+    /// different allocations. This can be used to deduplicate already interned
+    /// objects a second time, so that collections of them can be compared by
+    /// pointer.
+    ///
+    /// If an equal pointer has already been interned, the passed pointer will
+    /// be dropped, and a reference to the already interned pointer will be
     /// returned.
     ///
-    /// # Example
-    /// ```rust
-    /// # use refcount_interner::RcInterner;
-    /// let mut interner = RcInterner::new();
-    ///
-    /// let x = interner.intern_slice(&[1, 2, 3]);
-    ///
-    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
-    /// ```
-    pub fn intern_slice(&mut self, t: &[T]) -> Rc<[T]> {
-        if let Some(value) = self.0.get(t) {
-            value.clone()
-        } else {
-            let value: Rc<[T]> = Rc::from(t);
-            self.0.insert(value.clone());
-            value
-        }
-    }
-
-    /// Intern an owned vector
-    ///
-    /// If the slice behind the vector has already been interned, a reference
-    /// to the already / interned slice will be returned.
-    ///
-    /// If the slice has not yet been interned, the passed vector will be moved
-    /// into an `Rc<[T]>`, remembered for future calls to `intern()`, and
-    /// returned.
+    /// If no equal pointer has been interned yet, the passed pointer will be
+    /// moved into an `Rc<Rc<T>>`, remembered for future calls to `intern()`,
+    /// and returned.
     ///
     /// # Example
     /// ```rust
-    ///
+    /// # use std::rc::Rc;
     /// # use refcount_interner::RcInterner;
     /// let mut interner = RcInterner::new();
     ///
-    /// let v = vec![1, 2, 3];
-    /// let x = interner.intern_vec(v);
+    /// let x: Rc<str> = Rc::from("hello");
+    /// let y: Rc<str> = Rc::from("hello");
+    ///
+    /// let a = interner.intern_handle(x);
+    /// let b = interner.intern_handle(y);
     ///
-    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
+    /// assert!(Rc::ptr_eq(&a, &b));
     /// ```
-    pub fn intern_vec(&mut self, t: Vec<T>) -> Rc<[T]> {
-        self.intern_boxed(t.into_boxed_slice())
+    pub fn intern_handle(&mut self, h: Rc<T>) -> Rc<Rc<T>> {
+        self.intern(h)
     }
 }
 
-impl RcInterner<str> {
-    /// Intern a string slice
-    ///
-    /// This method can be used to intern string slices without boxing them.
-    ///
-    /// If the string slice has already been interned, a reference to the
-    /// already interned string slice will be returned.
-    ///
-    /// If the string slice has not yet been interned, the passed object will be
-    /// cloned into an `Rc<str>`, remembered for future calls to `intern()`, and
-    /// returned.
-    ///
-    /// # Example
-    /// ```rust
-    /// # use refcount_interner::RcInterner;
-    /// let mut interner = RcInterner::new();
-    ///
-    /// let x = interner.intern_str("hello");
-    ///
-    /// assert_eq!(x.as_ref(), "hello");
-    /// ```
-    pub fn intern_str(&mut self, t: &str) -> Rc<str> {
-        if let Some(value) = self.0.get(t) {
-            value.clone()
-        } else {
-            let value: Rc<str> = Rc::from(t);
-            self.0.insert(value.clone());
-            value
+/// Collect already allocated handles into a new interner
+///
+/// Each handle is interned with `intern_rc()`.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// # use refcount_interner::RcInterner;
+/// let x = Rc::new(42);
+/// let interner: RcInterner<i32> = vec![x.clone()].into_iter().collect();
+///
+/// assert!(Rc::ptr_eq(&interner.try_intern(&42).unwrap(), &x));
+/// ```
+impl<T, B> FromIterator<Rc<T>> for RcInterner<T, B>
+    where T: ?Sized + Hash + Eq, B: SetBackend<Rc<T>> + Default
+{
+    fn from_iter<I: IntoIterator<Item = Rc<T>>>(iter: I) -> RcInterner<T, B> {
+        let mut interner = RcInterner::default();
+        for value in iter {
+            interner.intern_rc(value);
         }
-    }
-
-    /// Intern an owned string
-    ///
-    /// If the string has already been interned, a reference to the already
-    /// interned string slice will be returned.
-    ///
-    /// If the string has not yet been interned, the passed string will be moved
-    /// into an `Rc<str>`, remembered for future calls to `intern()`, and
-    /// returned.
-    ///
-    /// # Example
-    /// ```rust
-    ///
-    /// # use refcount_interner::RcInterner;
-    /// let mut interner = RcInterner::new();
-    ///
-    /// let s = String::from("hello");
-    /// let x = interner.intern_string(s);
-    ///
-    /// assert_eq!(x.as_ref(), "hello");
-    /// ```
-    pub fn intern_string(&mut self, t: String) -> Rc<str> {
-        self.intern_boxed(t.into_boxed_str())
+        interner
     }
 }
@@ -0,0 +1,133 @@
+use std::rc::Rc;
+use std::fmt;
+
+use crate::RcInterner;
+
+/// A string interner consulting a backing source on misses
+///
+/// When a string slice has not been interned locally, the backing closure is
+/// called with it. If the backing returns a handle, that handle is adopted:
+/// it is remembered locally and returned, so the local interner shares the
+/// allocation of the backing source. If the backing returns `None`, the string
+/// slice is interned locally as usual.
+///
+/// This allows a small interner to sit in front of a larger shared one. Like
+/// with `RcInterner`, interned string slices will be deallocated when there
+/// are no references to them any more and `shrink_to_fit()` is called on the
+/// interner.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::{RcInterner, ReadThroughInterner};
+///
+/// let mut shared = RcInterner::new();
+/// let hello = shared.intern_str("hello");
+///
+/// let mut interner = ReadThroughInterner::new(|s: &str| shared.try_intern(s));
+///
+/// assert!(Rc::ptr_eq(&interner.intern("hello"), &hello));
+/// assert_eq!(interner.intern("world").as_ref(), "world");
+/// ```
+pub struct ReadThroughInterner<F> {
+    interner: RcInterner<str>,
+    backing: F,
+}
+
+impl<F: Fn(&str) -> Option<Rc<str>>> ReadThroughInterner<F> {
+    /// Create a new, empty interner consulting `backing` on misses.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ReadThroughInterner;
+    /// let mut interner = ReadThroughInterner::new(|_: &str| None);
+    /// # let x = interner.intern("hello");
+    /// ```
+    pub fn new(backing: F) -> ReadThroughInterner<F> {
+        ReadThroughInterner {
+            interner: RcInterner::new(),
+            backing,
+        }
+    }
+
+    /// Intern a string slice
+    ///
+    /// If the string slice has already been interned locally, a reference to
+    /// the already interned string slice will be returned.
+    ///
+    /// Otherwise, the backing is consulted. If it returns a handle to an equal
+    /// string slice, that handle is remembered for future calls to `intern()`
+    /// and returned. If not, the string slice is copied into a new `Rc<str>`,
+    /// remembered, and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::ReadThroughInterner;
+    /// let supplied: Rc<str> = Rc::from("supplied");
+    /// let backing = supplied.clone();
+    /// let mut interner = ReadThroughInterner::new(move |s: &str| {
+    ///     if s == "supplied" { Some(backing.clone()) } else { None }
+    /// });
+    ///
+    /// let x = interner.intern("supplied");
+    /// let y = interner.intern("fresh");
+    ///
+    /// assert!(Rc::ptr_eq(&x, &supplied));
+    /// assert!(Rc::ptr_eq(&x, &interner.intern("supplied")));
+    /// assert!(Rc::ptr_eq(&y, &interner.intern("fresh")));
+    /// ```
+    pub fn intern(&mut self, s: &str) -> Rc<str> {
+        if let Some(value) = self.interner.try_intern(s) {
+            return value;
+        }
+
+        match (self.backing)(s) {
+            Some(value) if value.as_ref() == s => {
+                self.interner.intern_rc(value)
+            }
+            _ => self.interner.intern_str(s),
+        }
+    }
+
+    /// Get a reference to the local interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ReadThroughInterner;
+    /// let mut interner = ReadThroughInterner::new(|_: &str| None);
+    ///
+    /// let x = interner.intern("hello");
+    /// assert_eq!(interner.interner().try_intern("hello"), Some(x));
+    /// ```
+    pub fn interner(&self) -> &RcInterner<str> {
+        &self.interner
+    }
+
+    /// Deallocate all locally interned string slices that are no longer
+    /// referenced and shrink the internal storage to fit.
+    ///
+    /// Adopted string slices are still referenced by the backing source if it
+    /// keeps them alive, but the local interner no longer remembers them.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ReadThroughInterner;
+    /// let mut interner = ReadThroughInterner::new(|_: &str| None);
+    ///
+    /// let x = interner.intern("hello");
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.interner().try_intern("hello"), None);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.interner.shrink_to_fit();
+    }
+}
+
+impl<F> fmt::Debug for ReadThroughInterner<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadThroughInterner").field(&self.interner).finish()
+    }
+}
@@ -0,0 +1,210 @@
+use std::rc::Rc;
+use std::hash::Hash;
+use std::cell::Cell;
+use std::collections::HashMap;
+#[cfg(any(test, feature = "debug-checks"))]
+use std::collections::HashSet;
+
+/// An interner returning reference-counted pointers to the interned data,
+/// which keeps track of how recently each interned object was used
+///
+/// Every call to `intern()` or `try_intern()` that finds an interned object
+/// marks it as the most recently used object. Unlike an LRU cache, objects
+/// are never evicted because of this; like with `RcInterner`, interned objects
+/// will be deallocated when there are no references to them any more and
+/// `shrink_to_fit()` is called on the interner.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::RecencyRcInterner;
+///
+/// let mut interner = RecencyRcInterner::new();
+///
+/// let a = interner.intern("a");
+/// let b = interner.intern("b");
+/// let c = interner.intern("c");
+/// interner.try_intern(&"a");
+///
+/// assert_eq!(interner.recency_rank(&"a"), Some(0));
+/// assert_eq!(interner.recency_rank(&"c"), Some(1));
+/// assert_eq!(interner.recency_rank(&"b"), Some(2));
+/// assert_eq!(interner.recency_rank(&"d"), None);
+/// ```
+#[derive(Debug)]
+pub struct RecencyRcInterner<T: ?Sized> {
+    entries: HashMap<Rc<T>, Cell<u64>>,
+    clock: Cell<u64>,
+}
+
+impl<T: ?Sized> Default for RecencyRcInterner<T> {
+    fn default() -> RecencyRcInterner<T> {
+        RecencyRcInterner {
+            entries: HashMap::new(),
+            clock: Cell::new(0),
+        }
+    }
+}
+
+impl<T: ?Sized + Hash + Eq> RecencyRcInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecencyRcInterner;
+    /// let mut interner = RecencyRcInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> RecencyRcInterner<T> {
+        Default::default()
+    }
+
+    fn tick(&self) -> u64 {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+        now
+    }
+
+    /// Attempt to get a reference to an already interned object, marking it
+    /// as the most recently used object.
+    ///
+    /// If the object has not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RecencyRcInterner;
+    /// let mut interner = RecencyRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(Rc::new(42)));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// ```
+    pub fn try_intern(&self, t: &T) -> Option<Rc<T>> {
+        let (value, used_at) = self.entries.get_key_value(t)?;
+        used_at.set(self.tick());
+        Some(value.clone())
+    }
+
+    /// Intern a boxed object, marking it as the most recently used object
+    ///
+    /// See `RcInterner::intern_boxed()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecencyRcInterner;
+    /// let mut interner = RecencyRcInterner::new();
+    ///
+    /// let x: Box<str> = Box::from("hello");
+    /// let y = interner.intern_boxed(x);
+    ///
+    /// assert_eq!(y.as_ref(), "hello");
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
+        if let Some(value) = self.try_intern(t.as_ref()) {
+            value
+        } else {
+            let value: Rc<T> = Rc::from(t);
+            let used_at = Cell::new(self.tick());
+            self.entries.insert(value.clone(), used_at);
+            value
+        }
+    }
+
+    /// Get the recency rank of an interned object
+    ///
+    /// The most recently used object has rank 0, the object used before it
+    /// has rank 1, and so on. If the object has not yet been interned, `None`
+    /// will be returned. Getting the rank does not mark the object as used.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecencyRcInterner;
+    /// let mut interner = RecencyRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    ///
+    /// assert_eq!(interner.recency_rank(&1337), Some(0));
+    /// assert_eq!(interner.recency_rank(&42), Some(1));
+    /// ```
+    pub fn recency_rank(&self, t: &T) -> Option<usize> {
+        let used_at = self.entries.get(t)?.get();
+        let rank = self.entries.values()
+            .filter(|other| other.get() > used_at)
+            .count();
+
+        Some(rank)
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecencyRcInterner;
+    /// let mut interner = RecencyRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.recency_rank(&42), None);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.entries.retain(|value, _| Rc::strong_count(value) > 1);
+        self.entries.shrink_to_fit();
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecencyRcInterner;
+    /// let mut interner = RecencyRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    /// interner.try_intern(&42);
+    /// interner.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        let mut stamps = HashSet::new();
+        for (value, used_at) in &self.entries {
+            let (found, _) = self.entries.get_key_value(value.as_ref())
+                .expect("interned object not found by lookup");
+            assert!(Rc::ptr_eq(found, value), "equal objects interned twice");
+            assert!(used_at.get() <= self.clock.get(), "use time ahead");
+            assert!(stamps.insert(used_at.get()), "use time not unique");
+        }
+    }
+}
+
+impl<T: Hash + Eq> RecencyRcInterner<T> {
+    /// Intern an owned object, marking it as the most recently used object
+    ///
+    /// See `RcInterner::intern()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RecencyRcInterner;
+    /// let mut interner = RecencyRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        if let Some(value) = self.try_intern(&t) {
+            value
+        } else {
+            let value = Rc::new(t);
+            let used_at = Cell::new(self.tick());
+            self.entries.insert(value.clone(), used_at);
+            value
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::rc::Rc;
+use std::hash::Hash;
+
+use crate::RcInterner;
+
+/// An operation recorded by a `RecordingRcInterner`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<T: ?Sized> {
+    /// An object was interned
+    Intern(Box<T>),
+    /// An interned object was removed from the interner
+    Remove(Box<T>),
+}
+
+/// An `RcInterner` that records every operation performed on it
+///
+/// The recorded operations can be retrieved with `take_log()` and replayed
+/// with `replay()` to rebuild an interner with the same contents.
+///
+/// Deallocating unreferenced objects with `shrink_to_fit()` is recorded as
+/// removal of each deallocated object, so replaying the log does not depend on
+/// which objects were still referenced at the time.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::RecordingRcInterner;
+///
+/// let mut interner = RecordingRcInterner::new();
+///
+/// let x = interner.intern(42);
+/// let y = interner.intern(1337);
+/// drop(y);
+/// interner.shrink_to_fit();
+///
+/// let replayed = RecordingRcInterner::replay(interner.take_log());
+/// assert!(replayed.try_intern(&42).is_some());
+/// assert!(replayed.try_intern(&1337).is_none());
+/// ```
+#[derive(Debug)]
+pub struct RecordingRcInterner<T: ?Sized> {
+    interner: RcInterner<T>,
+    log: Vec<Op<T>>,
+}
+
+impl<T: ?Sized> Default for RecordingRcInterner<T> {
+    fn default() -> RecordingRcInterner<T> {
+        RecordingRcInterner {
+            interner: Default::default(),
+            log: Vec::new(),
+        }
+    }
+}
+
+impl<T> RecordingRcInterner<T>
+    where T: ?Sized + Hash + Eq + ToOwned, T::Owned: Into<Box<T>>
+{
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecordingRcInterner;
+    /// let mut interner = RecordingRcInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> RecordingRcInterner<T> {
+        Default::default()
+    }
+
+    /// Create a new interner by applying all operations in the log.
+    ///
+    /// The operations are recorded in the log of the new interner as well.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RecordingRcInterner, Op};
+    /// let log = vec![
+    ///     Op::Intern(Box::from("hello")),
+    ///     Op::Intern(Box::from("world")),
+    ///     Op::Remove(Box::from("hello")),
+    /// ];
+    ///
+    /// let interner = RecordingRcInterner::<str>::replay(log);
+    /// assert!(interner.try_intern("hello").is_none());
+    /// assert!(interner.try_intern("world").is_some());
+    /// ```
+    pub fn replay(ops: Vec<Op<T>>) -> RecordingRcInterner<T> {
+        let mut interner = RecordingRcInterner::new();
+        for op in ops {
+            match op {
+                Op::Intern(t) => {
+                    interner.intern_boxed(t);
+                }
+                Op::Remove(t) => {
+                    interner.remove(t.as_ref());
+                }
+            }
+        }
+
+        interner
+    }
+
+    /// Attempt to get a reference to an already interned object.
+    ///
+    /// This is not recorded, see `RcInterner::try_intern()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RecordingRcInterner;
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(Rc::new(42)));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// ```
+    pub fn try_intern(&self, t: &T) -> Option<Rc<T>> {
+        self.interner.try_intern(t)
+    }
+
+    /// Intern a boxed object and record it, see `RcInterner::intern_boxed()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RecordingRcInterner, Op};
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x: Box<str> = Box::from("hello");
+    /// let y = interner.intern_boxed(x);
+    ///
+    /// assert_eq!(interner.take_log(), vec![Op::Intern(Box::from("hello"))]);
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
+        self.log.push(Op::Intern(t.as_ref().to_owned().into()));
+        self.interner.intern_boxed(t)
+    }
+
+    /// Remove an interned object and record it
+    ///
+    /// Outstanding references to the object stay valid, but are no longer
+    /// deduplicated against. Returns whether the object was interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecordingRcInterner;
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(interner.remove(&42));
+    /// assert!(!interner.remove(&42));
+    /// ```
+    pub fn remove(&mut self, t: &T) -> bool {
+        self.log.push(Op::Remove(t.to_owned().into()));
+        self.interner.remove(t)
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit, recording the removal of each deallocated
+    /// object.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RecordingRcInterner, Op};
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// drop(x);
+    /// interner.shrink_to_fit();
+    ///
+    /// assert_eq!(interner.take_log(), vec![
+    ///     Op::Intern(Box::new(42)),
+    ///     Op::Remove(Box::new(42)),
+    /// ]);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        for value in &self.interner.set {
+            if Rc::strong_count(value) == 1 {
+                let value = value.as_ref().to_owned().into();
+                self.log.push(Op::Remove(value));
+            }
+        }
+
+        self.interner.shrink_to_fit();
+    }
+
+    /// Take all operations recorded so far, leaving the log empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RecordingRcInterner, Op};
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.take_log(), vec![Op::Intern(Box::new(42))]);
+    /// assert_eq!(interner.take_log(), vec![]);
+    /// ```
+    pub fn take_log(&mut self) -> Vec<Op<T>> {
+        std::mem::take(&mut self.log)
+    }
+
+    /// Get a reference to the underlying interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecordingRcInterner;
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.interner().try_intern(&42), Some(x));
+    /// ```
+    pub fn interner(&self) -> &RcInterner<T> {
+        &self.interner
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RecordingRcInterner;
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// interner.remove(&42);
+    /// interner.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        self.interner.check_invariants();
+    }
+}
+
+impl<T: Hash + Eq + Clone> RecordingRcInterner<T> {
+    /// Intern an owned object and record it, see `RcInterner::intern()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RecordingRcInterner;
+    /// let mut interner = RecordingRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        self.log.push(Op::Intern(Box::new(t.clone())));
+        self.interner.intern(t)
+    }
+}
@@ -0,0 +1,177 @@
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+use std::fmt;
+use std::ops::Deref;
+use std::borrow::Borrow;
+
+/// A reference-counted pointer type, allowing code to be generic over `Rc`
+/// and `Arc`
+///
+/// `RefCountInterner<P>` interns objects behind any pointer type `P`
+/// implementing this trait. `RcInterner<T>` and `ArcInterner<T>` are
+/// `RefCountInterner<Rc<T>>` and `RefCountInterner<Arc<T>>` respectively.
+///
+/// # Example
+/// ```rust
+/// use std::rc::Rc;
+/// use std::sync::Arc;
+/// use std::hash::Hash;
+/// use refcount_interner::{Interner, RefCount, RefCountInterner};
+///
+/// fn count_shared<P>(words: &[&str]) -> usize
+///     where P: RefCount<Target = str> + Hash + Eq
+/// {
+///     let mut interner = RefCountInterner::<P>::default();
+///     let handles: Vec<P> = words.iter()
+///         .map(|&word| interner.intern_boxed(Box::from(word)))
+///         .collect();
+///
+///     handles.iter()
+///         .filter(|&handle| P::strong_count(handle) > 2)
+///         .count()
+/// }
+///
+/// let words = ["a", "b", "a", "c", "a"];
+/// assert_eq!(count_shared::<Rc<str>>(&words), 3);
+/// assert_eq!(count_shared::<Arc<str>>(&words), 3);
+/// ```
+pub trait RefCount: Deref + Clone
+    + AsRef<<Self as Deref>::Target> + Borrow<<Self as Deref>::Target>
+{
+    /// The weak pointer type belonging to this pointer type
+    type Weak: Clone + fmt::Debug;
+
+    /// The type of the callback passed to `on_low_hit_rate()`
+    ///
+    /// This is `dyn FnMut(f64)` for `Rc` and `dyn FnMut(f64) + Send + Sync`
+    /// for `Arc`, so that an `ArcInterner` can still be sent and shared
+    /// between threads.
+    type LowHitRateCallback: ?Sized + FnMut(f64);
+
+    /// Move an object into a new reference-counted allocation.
+    fn new(t: Self::Target) -> Self where Self::Target: Sized;
+
+    /// Move a boxed object into a new reference-counted allocation.
+    fn from_box(b: Box<Self::Target>) -> Self;
+
+    /// Create a weak pointer to the allocation.
+    fn downgrade(this: &Self) -> Self::Weak;
+
+    /// Attempt to get a strong pointer from a weak pointer.
+    ///
+    /// If the allocation has already been dropped, `None` will be returned.
+    fn upgrade(weak: &Self::Weak) -> Option<Self>;
+
+    /// Get the number of strong references to the allocation.
+    fn strong_count(this: &Self) -> usize;
+
+    /// Get the number of weak references to the allocation.
+    fn weak_count(this: &Self) -> usize;
+
+    /// Check whether two pointers point to the same allocation.
+    fn ptr_eq(this: &Self, other: &Self) -> bool;
+
+    /// Get a raw pointer to the object.
+    fn as_ptr(this: &Self) -> *const Self::Target;
+}
+
+impl<T: ?Sized> RefCount for Rc<T> {
+    type Weak = rc::Weak<T>;
+    type LowHitRateCallback = dyn FnMut(f64);
+
+    fn new(t: T) -> Rc<T> where T: Sized {
+        Rc::new(t)
+    }
+
+    fn from_box(b: Box<T>) -> Rc<T> {
+        Rc::from(b)
+    }
+
+    fn downgrade(this: &Rc<T>) -> rc::Weak<T> {
+        Rc::downgrade(this)
+    }
+
+    fn upgrade(weak: &rc::Weak<T>) -> Option<Rc<T>> {
+        weak.upgrade()
+    }
+
+    fn strong_count(this: &Rc<T>) -> usize {
+        Rc::strong_count(this)
+    }
+
+    fn weak_count(this: &Rc<T>) -> usize {
+        Rc::weak_count(this)
+    }
+
+    fn ptr_eq(this: &Rc<T>, other: &Rc<T>) -> bool {
+        Rc::ptr_eq(this, other)
+    }
+
+    fn as_ptr(this: &Rc<T>) -> *const T {
+        Rc::as_ptr(this)
+    }
+}
+
+impl<T: ?Sized> RefCount for Arc<T> {
+    type Weak = sync::Weak<T>;
+    type LowHitRateCallback = dyn FnMut(f64) + Send + Sync;
+
+    fn new(t: T) -> Arc<T> where T: Sized {
+        Arc::new(t)
+    }
+
+    fn from_box(b: Box<T>) -> Arc<T> {
+        Arc::from(b)
+    }
+
+    fn downgrade(this: &Arc<T>) -> sync::Weak<T> {
+        Arc::downgrade(this)
+    }
+
+    fn upgrade(weak: &sync::Weak<T>) -> Option<Arc<T>> {
+        weak.upgrade()
+    }
+
+    fn strong_count(this: &Arc<T>) -> usize {
+        Arc::strong_count(this)
+    }
+
+    fn weak_count(this: &Arc<T>) -> usize {
+        Arc::weak_count(this)
+    }
+
+    fn ptr_eq(this: &Arc<T>, other: &Arc<T>) -> bool {
+        Arc::ptr_eq(this, other)
+    }
+
+    fn as_ptr(this: &Arc<T>) -> *const T {
+        Arc::as_ptr(this)
+    }
+}
+
+/// A function that can be registered with `on_low_hit_rate()`
+///
+/// This converts the function into the boxed callback type `C` of the
+/// pointer type, see `RefCount::LowHitRateCallback`. Any `FnMut(f64)` can be
+/// registered with an `RcInterner`, while an `ArcInterner` also requires it to
+/// be `Send` and `Sync`.
+pub trait IntoLowHitRateCallback<C: ?Sized> {
+    /// Box this function as a callback of type `C`.
+    fn into_callback(self) -> Box<C>;
+}
+
+impl<F> IntoLowHitRateCallback<dyn FnMut(f64)> for F
+    where F: FnMut(f64) + 'static
+{
+    fn into_callback(self) -> Box<dyn FnMut(f64)> {
+        Box::new(self)
+    }
+}
+
+impl<F> IntoLowHitRateCallback<dyn FnMut(f64) + Send + Sync> for F
+    where F: FnMut(f64) + Send + Sync + 'static
+{
+    fn into_callback(self) -> Box<dyn FnMut(f64) + Send + Sync> {
+        Box::new(self)
+    }
+}
@@ -0,0 +1,3384 @@
+use std::fmt;
+use std::ops::Deref;
+use std::io::{self, Read};
+use std::mem;
+use std::borrow::Cow;
+use std::iter::FromIterator;
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::hash::{Hash, BuildHasher};
+use std::collections::{HashSet, HashMap};
+
+use crate::hit_rate::HitRateMonitor;
+use crate::generation::GenTaggedHandle;
+#[cfg(debug_assertions)]
+use crate::generation::next_interner_id;
+use crate::bloom::BloomFilter;
+use crate::interner::Interner;
+use crate::backend::SetBackend;
+use crate::canon::CanonRule;
+use crate::checkpoint::Checkpoint;
+use crate::diff::InternerDiff;
+use crate::metrics::{InternerMetrics, InternerStats};
+use crate::perfect::PerfectHashInterner;
+use crate::refcount::{RefCount, IntoLowHitRateCallback};
+use crate::internable::Internable;
+use crate::handle::{RefCountHandle, InternedRef};
+
+/// Maximum number of boxes kept for reuse by `boxed()`
+const MAX_FREE_BOXES: usize = 64;
+
+/// An interner returning reference-counted pointers of type `P` to the
+/// interned data
+///
+/// Interned objects will be deallocated when there are no references to them
+/// any more and `shrink_to_fit()` is called on the interner
+///
+/// This interner is usually used through the `RcInterner<T>` and
+/// `ArcInterner<T>` aliases, which return `Rc<T>` and `Arc<T>` respectively.
+/// Code that works with both can be generic over the pointer type with the
+/// `RefCount` trait.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+///
+/// let x = interner.intern(42);
+/// let y = interner.intern(1337);
+///
+/// assert_eq!(*x, 42);
+/// assert_ne!(x, y);
+/// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+/// ```
+///
+/// The interned objects are stored in a `HashSet` by default. A custom hasher
+/// can be used with `with_hasher()`. Other sets can be used by implementing
+/// `SetBackend` for them, see `with_backend()`.
+pub struct RefCountInterner<P, T: ?Sized = <P as Deref>::Target, B = HashSet<P>>
+    where P: RefCount
+{
+    pub(crate) set: B,
+    generation: u64,
+    #[cfg(debug_assertions)]
+    id: u64,
+    bloom: Option<BloomFilter>,
+    canonicalization: CanonRule,
+    capacity_frozen: bool,
+    journal: Option<Vec<P::Weak>>,
+    checkpoints: Vec<usize>,
+    displaced: Vec<P::Weak>,
+    hit_rate: Option<HitRateMonitor<P::LowHitRateCallback>>,
+    hits: u64,
+    misses: u64,
+    auto_shrink: Option<usize>,
+    inserts_since_shrink: usize,
+    free_boxes: Option<Vec<Box<T>>>,
+}
+
+impl<P, T, B> fmt::Debug for RefCountInterner<P, T, B>
+    where P: RefCount, T: ?Sized + fmt::Debug, B: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("RefCountInterner");
+        debug.field("set", &self.set)
+            .field("generation", &self.generation);
+        #[cfg(debug_assertions)]
+        debug.field("id", &self.id);
+        debug.field("bloom", &self.bloom)
+            .field("canonicalization", &self.canonicalization)
+            .field("capacity_frozen", &self.capacity_frozen)
+            .field("journal", &self.journal)
+            .field("checkpoints", &self.checkpoints)
+            .field("displaced", &self.displaced)
+            .field("hit_rate", &self.hit_rate)
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .field("auto_shrink", &self.auto_shrink)
+            .field("inserts_since_shrink", &self.inserts_since_shrink)
+            .field("free_boxes", &self.free_boxes)
+            .finish()
+    }
+}
+
+impl<P, T, B> Default for RefCountInterner<P, T, B>
+    where P: RefCount, T: ?Sized, B: Default
+{
+    fn default() -> RefCountInterner<P, T, B> {
+        RefCountInterner::from_set(B::default())
+    }
+}
+
+/// Clone the interner
+///
+/// The clone holds handles to the same interned objects as the original, but
+/// both interners intern new objects independently afterwards. Checkpoints,
+/// the callback of `on_low_hit_rate()` and boxes kept for reuse are not
+/// cloned.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// # use refcount_interner::RcInterner;
+/// let mut interner = RcInterner::new();
+/// let x = interner.intern(42);
+///
+/// let mut clone = interner.clone();
+/// assert_eq!(clone.try_intern(&42), Some(x.clone()));
+/// assert!(Rc::ptr_eq(&x, &clone.intern(42)));
+///
+/// let y = clone.intern(1337);
+/// assert_eq!(interner.try_intern(&1337), None);
+/// ```
+impl<P, T, B> Clone for RefCountInterner<P, T, B>
+    where P: RefCount, T: ?Sized, B: Clone
+{
+    fn clone(&self) -> RefCountInterner<P, T, B> {
+        RefCountInterner {
+            set: self.set.clone(),
+            generation: self.generation,
+            #[cfg(debug_assertions)]
+            id: next_interner_id(),
+            bloom: self.bloom.clone(),
+            canonicalization: self.canonicalization,
+            capacity_frozen: self.capacity_frozen,
+            journal: None,
+            checkpoints: Vec::new(),
+            displaced: self.displaced.iter()
+                .filter(|weak| P::upgrade(weak).is_some())
+                .cloned()
+                .collect(),
+            hit_rate: None,
+            hits: self.hits,
+            misses: self.misses,
+            auto_shrink: self.auto_shrink,
+            inserts_since_shrink: self.inserts_since_shrink,
+            free_boxes: self.free_boxes.as_ref().map(|_| Vec::new()),
+        }
+    }
+}
+
+impl<P: RefCount, T: ?Sized, B> RefCountInterner<P, T, B> {
+    fn from_set(set: B) -> RefCountInterner<P, T, B> {
+        RefCountInterner {
+            set,
+            generation: 0,
+            #[cfg(debug_assertions)]
+            id: next_interner_id(),
+            bloom: None,
+            canonicalization: CanonRule::FirstSeen,
+            capacity_frozen: false,
+            journal: None,
+            checkpoints: Vec::new(),
+            displaced: Vec::new(),
+            hit_rate: None,
+            hits: 0,
+            misses: 0,
+            auto_shrink: None,
+            inserts_since_shrink: 0,
+            free_boxes: None,
+        }
+    }
+}
+
+impl<P, T> RefCountInterner<P, T>
+    where P: RefCount<Target = T> + Hash + Eq, T: ?Sized + Hash + Eq
+{
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> RefCountInterner<P, T> {
+        Default::default()
+    }
+
+    /// Create a new, empty interner with space for at least `capacity`
+    /// interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::with_capacity(100);
+    ///
+    /// let capacity = interner.capacity();
+    /// assert!(capacity >= 100);
+    ///
+    /// for i in 0..100 {
+    ///     interner.intern(i);
+    /// }
+    /// assert_eq!(interner.capacity(), capacity);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> RefCountInterner<P, T> {
+        RefCountInterner::from_set(HashSet::with_capacity(capacity))
+    }
+}
+
+impl<P, T, B> RefCountInterner<P, T, B>
+    where P: RefCount<Target = T>, T: ?Sized + Hash + Eq, B: SetBackend<P>
+{
+    /// Create a new interner storing the interned objects in `set`
+    ///
+    /// Objects already in `set` are treated as interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::collections::HashSet;
+    /// # use refcount_interner::RcInterner;
+    /// let set = HashSet::with_capacity(100);
+    /// let mut interner = RcInterner::with_backend(set);
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn with_backend(set: B) -> RefCountInterner<P, T, B> {
+        RefCountInterner::from_set(set)
+    }
+
+    /// Get the capacity of the internal storage when created with room for
+    /// `n` objects.
+    ///
+    /// The capacity is at least `n`, and depends on how the backend sizes its
+    /// storage, e.g. `HashSet` rounds up to a power of two buckets, of which
+    /// 7/8 can be used. Interning up to `capacity_for(n)` objects into an
+    /// interner created with `with_capacity(n)` does not grow its storage.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let capacity = RcInterner::<u32>::capacity_for(1000);
+    /// assert!(capacity >= 1000);
+    ///
+    /// let mut interner = RcInterner::with_capacity(1000);
+    /// assert_eq!(interner.capacity(), capacity);
+    ///
+    /// for i in 0..capacity as u32 {
+    ///     interner.intern(i);
+    /// }
+    /// assert_eq!(interner.capacity(), capacity);
+    ///
+    /// for n in 0..100 {
+    ///     let interner = RcInterner::<u32>::with_capacity(n);
+    ///     assert_eq!(interner.capacity(), RcInterner::<u32>::capacity_for(n));
+    /// }
+    /// ```
+    pub fn capacity_for(n: usize) -> usize {
+        B::capacity_for(n)
+    }
+
+    /// Get the number of objects the interner can hold without growing its
+    /// internal storage.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let interner = RcInterner::<u32>::with_capacity(100);
+    /// assert!(interner.capacity() >= 100);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.set.capacity()
+    }
+
+    /// Get the number of interned objects.
+    ///
+    /// This includes interned objects that are no longer referenced outside
+    /// of the interner, but have not yet been deallocated by
+    /// `shrink_to_fit()`. Use `live_len()` to count only referenced objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// interner.intern(1337);
+    /// assert_eq!(interner.len(), 2);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Check whether no objects are interned.
+    ///
+    /// Like `len()`, this counts interned objects that are no longer
+    /// referenced outside of the interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// assert!(interner.is_empty());
+    ///
+    /// interner.intern(42);
+    /// assert!(!interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Get the number of interned objects that are still referenced outside
+    /// of the interner.
+    ///
+    /// Unlike `len()`, this scans all interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// interner.intern(1337);
+    ///
+    /// assert_eq!(interner.len(), 2);
+    /// assert_eq!(interner.live_len(), 1);
+    /// ```
+    pub fn live_len(&self) -> usize {
+        self.set.iter()
+            .filter(|value| P::strong_count(value) > 1)
+            .count()
+    }
+
+    /// Get a snapshot of the metrics of the interner
+    ///
+    /// Hits and misses are counted for all intern calls, but not for
+    /// `try_intern()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(42);
+    ///
+    /// let metrics = interner.metrics();
+    /// assert_eq!(metrics.len(), 1);
+    /// assert_eq!(metrics.collectible(), 0);
+    /// assert_eq!((metrics.hits(), metrics.misses()), (1, 1));
+    /// ```
+    pub fn metrics(&self) -> InternerMetrics {
+        InternerMetrics {
+            len: self.set.len(),
+            capacity: self.set.capacity(),
+            collectible: self.set.iter()
+                .filter(|value| P::strong_count(value) == 1)
+                .count(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    /// Count the live and dead interned objects
+    ///
+    /// This scans all interned objects. See `InternerStats`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// interner.intern(1337);
+    ///
+    /// let stats = interner.stats();
+    /// assert_eq!((stats.total(), stats.live(), stats.dead()), (2, 1, 1));
+    /// ```
+    pub fn stats(&self) -> InternerStats {
+        InternerStats {
+            total: self.set.len(),
+            live: self.live_len(),
+        }
+    }
+
+    /// Render the metrics of the interner in the Prometheus text exposition
+    /// format
+    ///
+    /// The number of interned objects, the capacity and the number of
+    /// collectible objects are rendered as gauges, and the hits and misses as
+    /// counters. Every metric name starts with `prefix`, followed by an
+    /// underscore.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(42);
+    /// interner.intern(1337);
+    ///
+    /// let text = interner.metrics_prometheus("symbols");
+    /// assert!(text.contains("# TYPE symbols_len gauge\nsymbols_len 2\n"));
+    /// assert!(text.contains("symbols_collectible 1\n"));
+    /// assert!(text.contains("# TYPE symbols_hits_total counter\n"));
+    /// assert!(text.contains("symbols_hits_total 1\n"));
+    /// assert!(text.contains("symbols_misses_total 2\n"));
+    /// ```
+    pub fn metrics_prometheus(&self, prefix: &str) -> String {
+        self.metrics().to_prometheus(prefix)
+    }
+
+    /// Iterate over the interned objects.
+    ///
+    /// The iteration order is unspecified. Interned objects that are no longer
+    /// referenced outside of the interner are included until they are
+    /// deallocated by `shrink_to_fit()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_str("hello");
+    /// let y = interner.intern_str("world");
+    ///
+    /// let mut strings: Vec<&str> = interner.iter()
+    ///     .map(|value| value.as_ref())
+    ///     .collect();
+    /// strings.sort();
+    ///
+    /// assert_eq!(strings, vec!["hello", "world"]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &P> + '_ {
+        self.set.iter()
+    }
+
+    /// Iterate over the interned objects together with the number of
+    /// references to them outside of the interner.
+    ///
+    /// The iteration order is unspecified.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = x.clone();
+    /// drop(interner.intern(1337));
+    ///
+    /// let mut counts: Vec<_> = interner.iter_with_refcounts()
+    ///     .map(|(value, count)| (**value, count))
+    ///     .collect();
+    /// counts.sort();
+    ///
+    /// assert_eq!(counts, vec![(42, 2), (1337, 0)]);
+    /// ```
+    pub fn iter_with_refcounts(&self)
+        -> impl Iterator<Item = (&P, usize)> + '_
+    {
+        self.set.iter().map(|value| (value, P::strong_count(value) - 1))
+    }
+
+    /// Compare the interned objects of two interners by value
+    ///
+    /// Objects interned in `self` but not in `other` are returned as added,
+    /// objects interned in `other` but not in `self` as removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut before = RcInterner::<str>::new();
+    /// before.intern_str("a");
+    /// before.intern_str("b");
+    ///
+    /// let mut after = before.clone();
+    /// after.remove("a");
+    /// after.intern_str("c");
+    ///
+    /// let diff = after.diff(&before);
+    /// assert_eq!(diff.added, vec![Rc::from("c")]);
+    /// assert_eq!(diff.removed, vec![Rc::from("a")]);
+    /// ```
+    pub fn diff(&self, other: &RefCountInterner<P, T, B>) -> InternerDiff<P> {
+        let missing = |from: &RefCountInterner<P, T, B>,
+                       to: &RefCountInterner<P, T, B>| {
+            from.set.iter()
+                .filter(|value| to.set.get(value.as_ref()).is_none())
+                .cloned()
+                .collect()
+        };
+
+        InternerDiff {
+            added: missing(self, other),
+            removed: missing(other, self),
+        }
+    }
+
+    /// Attempt to get a reference to an already interned object.
+    ///
+    /// If the object has already been interned, an option containing a
+    /// reference to the already interned object will be returned.
+    ///
+    /// If the object has not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(Rc::new(42)));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// ```
+    pub fn try_intern(&self, t: &T) -> Option<P> {
+        self.set.get(t).cloned()
+    }
+
+    /// Check whether an object has already been interned.
+    ///
+    /// Unlike `try_intern()`, this does not clone the handle to the interned
+    /// object.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_str("hello");
+    /// assert!(interner.contains("hello"));
+    /// assert!(!interner.contains("world"));
+    /// ```
+    pub fn contains(&self, t: &T) -> bool {
+        self.set.get(t).is_some()
+    }
+
+    /// Get the number of references to an interned object outside the
+    /// interner.
+    ///
+    /// This is the strong count of the interned object, minus the reference
+    /// held by the interner. An interned object with zero references is
+    /// deallocated by the next call to `shrink_to_fit()`. If the object has
+    /// not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = x.clone();
+    /// interner.intern(1337);
+    ///
+    /// assert_eq!(interner.strong_count(&42), Some(2));
+    /// assert_eq!(interner.strong_count(&1337), Some(0));
+    /// assert_eq!(interner.strong_count(&7), None);
+    /// ```
+    pub fn strong_count(&self, t: &T) -> Option<usize> {
+        self.set.get(t).map(|value| P::strong_count(value) - 1)
+    }
+
+    /// Intern a boxed object
+    ///
+    /// This method must be used to intern unsized types, since unsized types
+    /// cannot be passed to `intern()`. The two most common unsized types,
+    /// `&[T]` and `&str` can be interned with `intern_slice()` and
+    /// `intern_str()` as well.
+    ///
+    /// If the object has already been interned, the passed object will be
+    /// dropped and deallocated, and a reference to the already interned object
+    /// will be returned. If box reuse has been enabled with `reuse_boxes()`,
+    /// the box is kept for reuse by `boxed()` instead of being deallocated.
+    ///
+    /// If the object has not yet been interned, the passed object will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = Box::new(42);
+    /// let y = interner.intern_boxed(x);
+    ///
+    /// assert_eq!(*y, 42);
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> P {
+        if let Some(value) = self.lookup(t.as_ref()) {
+            if let Some(free_boxes) = &mut self.free_boxes {
+                if free_boxes.len() < MAX_FREE_BOXES {
+                    free_boxes.push(t);
+                }
+            }
+            value
+        } else {
+            let value = P::from_box(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern a borrowed object, converting it into an owned object if it has
+    /// not yet been interned
+    ///
+    /// This works for all types whose owned form can be converted into a
+    /// `Box<T>`, such as `str`, `[T]` and cloneable sized types, and
+    /// generalizes `intern_str()`, `intern_slice()` and `intern_cloned()`.
+    ///
+    /// If the object has already been interned, a reference to the already
+    /// interned object will be returned without allocating.
+    ///
+    /// If the object has not yet been interned, its owned form will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::borrow::Borrow;
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut strs = RcInterner::new();
+    /// let x = strs.intern_borrowed("hello");
+    /// assert!(Rc::ptr_eq(&x, &strs.intern_borrowed("hello")));
+    ///
+    /// let mut slices = RcInterner::new();
+    /// let y = slices.intern_borrowed(&[1, 2, 3][..]);
+    /// assert!(Rc::ptr_eq(&y, &slices.intern_borrowed(&[1, 2, 3][..])));
+    ///
+    /// #[derive(Debug, PartialEq, Eq, Hash)]
+    /// #[repr(transparent)]
+    /// struct Name(str);
+    ///
+    /// struct NameBuf(String);
+    ///
+    /// impl Borrow<Name> for NameBuf {
+    ///     fn borrow(&self) -> &Name {
+    ///         unsafe { &*(self.0.as_str() as *const str as *const Name) }
+    ///     }
+    /// }
+    ///
+    /// impl ToOwned for Name {
+    ///     type Owned = NameBuf;
+    ///
+    ///     fn to_owned(&self) -> NameBuf {
+    ///         NameBuf(self.0.to_owned())
+    ///     }
+    /// }
+    ///
+    /// impl From<NameBuf> for Box<Name> {
+    ///     fn from(name: NameBuf) -> Box<Name> {
+    ///         let raw = Box::into_raw(name.0.into_boxed_str());
+    ///         unsafe { Box::from_raw(raw as *mut Name) }
+    ///     }
+    /// }
+    ///
+    /// let name = |s: &str| unsafe { &*(s as *const str as *const Name) };
+    ///
+    /// let mut names = RcInterner::new();
+    /// let a = names.intern_borrowed(name("alice"));
+    /// let b = names.intern_borrowed(name("bob"));
+    ///
+    /// assert!(Rc::ptr_eq(&a, &names.intern_borrowed(name("alice"))));
+    /// assert!(!Rc::ptr_eq(&a, &b));
+    /// assert_eq!(&a.0, "alice");
+    /// ```
+    pub fn intern_borrowed(&mut self, t: &T) -> P
+    where
+        T: ToOwned,
+        T::Owned: Into<Box<T>>,
+    {
+        if let Some(value) = self.lookup(t) {
+            value
+        } else {
+            let value = P::from_box(t.to_owned().into());
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Set the rule deciding which allocation becomes canonical when
+    /// `intern_shared()` is called with a handle equal to an interned object.
+    ///
+    /// The default rule is `CanonRule::FirstSeen`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, CanonRule};
+    /// let mut interner = RcInterner::<u32>::new();
+    /// assert_eq!(interner.canonicalization(), CanonRule::FirstSeen);
+    ///
+    /// interner.set_canonicalization(CanonRule::LowestAddress);
+    /// assert_eq!(interner.canonicalization(), CanonRule::LowestAddress);
+    /// ```
+    pub fn set_canonicalization(&mut self, rule: CanonRule) {
+        self.canonicalization = rule;
+    }
+
+    /// Get the rule deciding which allocation becomes canonical in
+    /// `intern_shared()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, CanonRule};
+    /// let interner = RcInterner::<u32>::new();
+    /// assert_eq!(interner.canonicalization(), CanonRule::FirstSeen);
+    /// ```
+    pub fn canonicalization(&self) -> CanonRule {
+        self.canonicalization
+    }
+
+    /// Intern an already allocated handle
+    ///
+    /// If no equal object has been interned yet, the passed handle is
+    /// remembered for future calls to `intern()` and returned.
+    ///
+    /// If an equal object has already been interned, the canonicalization rule
+    /// decides which allocation wins. With `CanonRule::FirstSeen`, the already
+    /// interned object is returned. With `CanonRule::LowestAddress`, the
+    /// allocation with the lower address is kept and returned; if that is the
+    /// passed handle, it replaces the interned object, and references to the
+    /// previously interned object are no longer canonical.
+    ///
+    /// This is also available as `intern_rc()` on an `RcInterner` and as
+    /// `intern_arc()` on an `ArcInterner`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use refcount_interner::ArcInterner;
+    /// let a = Arc::new(42);
+    /// let mut interner = ArcInterner::new();
+    ///
+    /// assert!(Arc::ptr_eq(&interner.intern_shared(a.clone()), &a));
+    /// assert!(Arc::ptr_eq(&interner.intern_shared(Arc::new(42)), &a));
+    /// ```
+    pub fn intern_shared(&mut self, value: P) -> P {
+        match self.lookup(&value) {
+            Some(interned) if P::ptr_eq(&interned, &value) => interned,
+            Some(interned) if self.canonicalization == CanonRule::LowestAddress
+                && address(&value) < address(&interned) =>
+            {
+                self.set.replace(value.clone());
+                self.displace(&interned);
+                self.generation += 1;
+                value
+            }
+            Some(interned) => {
+                self.displace(&value);
+                interned
+            }
+            None => {
+                self.insert(value.clone());
+                value
+            }
+        }
+    }
+
+    /// Map allocations that lost canonicalization to the canonical allocation
+    /// of their value
+    ///
+    /// When `intern_shared()` is called with a handle equal to, but not the
+    /// same allocation as an interned object, one of the two allocations stays
+    /// canonical, and the other one is remembered as displaced. This returns
+    /// a map from the address of every displaced allocation that is still
+    /// referenced to the canonical allocation, so that callers can rewrite
+    /// their references to use the canonical allocation. The interner
+    /// forgets the displaced allocations afterwards.
+    ///
+    /// Displaced allocations that are no longer referenced are also forgotten
+    /// whenever the list of displaced allocations would have to grow, so it
+    /// stays proportional to the number of displaced allocations that are
+    /// still referenced, even if `coalesce()` is never called.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let a = Rc::new(42);
+    /// let b = Rc::new(42);
+    ///
+    /// let mut interner: RcInterner<i32> = vec![a.clone(), b.clone()]
+    ///     .into_iter()
+    ///     .collect();
+    /// let remap = interner.coalesce();
+    ///
+    /// assert_eq!(interner.iter_with_refcounts().count(), 1);
+    /// assert!(Rc::ptr_eq(&remap[&Rc::as_ptr(&b)], &a));
+    /// assert!(!remap.contains_key(&Rc::as_ptr(&a)));
+    /// assert!(interner.coalesce().is_empty());
+    /// ```
+    pub fn coalesce(&mut self) -> HashMap<*const T, P> {
+        let mut remap = HashMap::new();
+        for weak in mem::take(&mut self.displaced) {
+            if let Some(value) = P::upgrade(&weak) {
+                if let Some(interned) = self.set.get(value.as_ref()) {
+                    if !P::ptr_eq(interned, &value) {
+                        remap.insert(P::as_ptr(&value), interned.clone());
+                    }
+                }
+            }
+        }
+
+        remap
+    }
+
+    /// Intern an object, lazily building the boxed object if it has not yet
+    /// been interned
+    ///
+    /// This can be used to lazily construct unsized objects, which cannot be
+    /// passed to `intern()`.
+    ///
+    /// If the object has already been interned, a reference to the already
+    /// interned object will be returned, and `build` will not be called.
+    ///
+    /// If the object has not yet been interned, `build` will be called to
+    /// produce a boxed object, which will be moved into a new `P`, remembered
+    /// for future calls to `intern()`, and returned. The boxed object must be
+    /// equal to `key`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner: RcInterner<[u8]> = RcInterner::new();
+    ///
+    /// let mut calls = 0;
+    /// let x = interner.get_or_intern_boxed_with(b"hello", || {
+    ///     calls += 1;
+    ///     Box::from(&b"hello"[..])
+    /// });
+    /// let y = interner.get_or_intern_boxed_with(b"hello", || unreachable!());
+    ///
+    /// assert_eq!(calls, 1);
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn get_or_intern_boxed_with<F>(&mut self, key: &T, build: F) -> P
+        where F: FnOnce() -> Box<T>
+    {
+        if let Some(value) = self.lookup(key) {
+            value
+        } else {
+            let value = P::from_box(build());
+            debug_assert!(value.as_ref() == key,
+                "built value differs from key");
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern any value that can be interned into this interner
+    ///
+    /// This dispatches to the matching `intern_*()` method for the type of
+    /// the value, see `Internable`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut strings = RcInterner::<str>::new();
+    /// let x = strings.intern_any("x");
+    /// let y = strings.intern_any(String::from("x"));
+    /// assert!(Rc::ptr_eq(&x, &y));
+    ///
+    /// let mut slices = RcInterner::<[i32]>::new();
+    /// let x = slices.intern_any(vec![1, 2]);
+    /// let y = slices.intern_any(&[1, 2][..]);
+    /// assert!(Rc::ptr_eq(&x, &y));
+    ///
+    /// let mut numbers = RcInterner::<i32>::new();
+    /// let x = numbers.intern_any(42);
+    /// assert!(Rc::ptr_eq(&x, &numbers.intern(42)));
+    /// ```
+    pub fn intern_any<V: Internable<T>>(&mut self, value: V) -> P {
+        value.intern_into(self)
+    }
+
+    /// Intern any value that can be interned into this interner, returning an
+    /// opaque handle
+    ///
+    /// This behaves like `intern_any()`, but returns a `RefCountHandle<P>`
+    /// instead of a `P`. Code that only uses opaque handles does not depend
+    /// on the representation of interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::<str>::new();
+    ///
+    /// let x = interner.intern_opaque("hello");
+    /// let y = interner.intern_opaque(String::from("hello"));
+    ///
+    /// assert_eq!(x, y);
+    /// assert_eq!(x.len(), 5);
+    /// ```
+    pub fn intern_opaque<V>(&mut self, value: V) -> RefCountHandle<P>
+        where V: Internable<T>
+    {
+        RefCountHandle {
+            value: value.intern_into(self),
+            #[cfg(debug_assertions)]
+            interner_id: self.id,
+        }
+    }
+
+    /// Check whether interning a new object would grow the internal storage
+    ///
+    /// If this returns `true`, the next call that interns an object that has
+    /// not yet been interned will have to reallocate and rehash the internal
+    /// storage. Call `preallocate_next()` at a convenient point to perform this
+    /// growth ahead of time.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// assert!(interner.would_grow_on_insert());
+    ///
+    /// let mut i = 0;
+    /// interner.preallocate_next();
+    /// while !interner.would_grow_on_insert() {
+    ///     interner.intern(i);
+    ///     i += 1;
+    /// }
+    ///
+    /// assert!(i > 0);
+    /// ```
+    pub fn would_grow_on_insert(&self) -> bool {
+        self.set.len() == self.set.capacity()
+    }
+
+    /// Grow the internal storage ahead of time if the next insertion would
+    /// have to grow it
+    ///
+    /// After calling this method, `would_grow_on_insert()` will return
+    /// `false`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let mut i = 0;
+    /// while i == 0 || !interner.would_grow_on_insert() {
+    ///     interner.intern(i);
+    ///     i += 1;
+    /// }
+    ///
+    /// interner.preallocate_next();
+    /// assert!(!interner.would_grow_on_insert());
+    /// ```
+    pub fn preallocate_next(&mut self) {
+        if self.would_grow_on_insert() {
+            self.set.reserve(1);
+        }
+    }
+
+    /// Call a function whenever the hit rate of intern calls drops below a
+    /// threshold
+    ///
+    /// The hit rate is the fraction of the last `window` intern calls that
+    /// returned an already interned object. Once `window` calls have been
+    /// made, the hit rate is checked after every call, and `f` is called with
+    /// it if it is below `threshold`. Calls to `try_intern()` are not counted.
+    ///
+    /// The function must be `Send` and `Sync` if `P` is `Arc`, see
+    /// `IntoLowHitRateCallback`. Calling this method again replaces the
+    /// previously registered function.
+    ///
+    /// # Panics
+    /// Panics if `window` is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::cell::RefCell;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let rates = Rc::new(RefCell::new(Vec::new()));
+    /// let r = rates.clone();
+    /// interner.on_low_hit_rate(4, 0.5, move |rate| r.borrow_mut().push(rate));
+    ///
+    /// for _ in 0..8 {
+    ///     interner.intern(42);
+    /// }
+    /// assert!(rates.borrow().is_empty());
+    ///
+    /// for i in 0..4 {
+    ///     interner.intern(i);
+    /// }
+    /// assert_eq!(*rates.borrow(), vec![0.25, 0.0]);
+    /// ```
+    ///
+    /// A burst of misses is noticed even if it does not line up with a
+    /// multiple of `window` calls:
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::cell::RefCell;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let rates = Rc::new(RefCell::new(Vec::new()));
+    /// let r = rates.clone();
+    /// interner.on_low_hit_rate(4, 0.5, move |rate| r.borrow_mut().push(rate));
+    ///
+    /// for _ in 0..6 {
+    ///     interner.intern(42);
+    /// }
+    /// for i in 1..4 {
+    ///     interner.intern(i);
+    /// }
+    /// for _ in 0..3 {
+    ///     interner.intern(42);
+    /// }
+    ///
+    /// assert_eq!(*rates.borrow(), vec![0.25, 0.25]);
+    /// ```
+    pub fn on_low_hit_rate<F>(&mut self, window: usize, threshold: f64, f: F)
+        where F: FnMut(f64) + IntoLowHitRateCallback<P::LowHitRateCallback>
+    {
+        let callback = f.into_callback();
+        self.hit_rate = Some(HitRateMonitor::new(window, threshold, callback));
+    }
+
+    /// Get the current generation of the interner
+    ///
+    /// The generation starts at zero and is incremented every time
+    /// `shrink_to_fit()` deallocates interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.generation(), 0);
+    ///
+    /// drop(x);
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.generation(), 1);
+    /// ```
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Create a generation-tagged handle to an interned object
+    ///
+    /// The handle remembers the current generation of the interner and keeps
+    /// the interned object alive.
+    ///
+    /// # Panics
+    /// In debug builds, panics if `value` is not the object interned in this
+    /// interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let handle = interner.tag(&x);
+    ///
+    /// assert!(Rc::ptr_eq(&x, handle.get()));
+    /// ```
+    #[track_caller]
+    pub fn tag(&self, value: &P) -> GenTaggedHandle<P> {
+        debug_assert!(self.is_canonical(value),
+            "tagged object is not interned in this interner");
+
+        GenTaggedHandle {
+            value: value.clone(),
+            generation: self.generation,
+            #[cfg(debug_assertions)]
+            interner: self.id,
+        }
+    }
+
+    /// Check whether a generation-tagged handle still refers to the interned
+    /// object
+    ///
+    /// If the generation of the interner has not changed since the handle was
+    /// created, no object has been removed or replaced, and the handle is
+    /// valid without a lookup. Otherwise, returns `false` if the object the
+    /// handle refers to is not the object interned in this interner any more,
+    /// e.g. because it was removed and an equal object was interned again.
+    ///
+    /// # Panics
+    /// In debug builds, panics if the handle was created by a different
+    /// interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    /// let handle = interner.tag(&x);
+    ///
+    /// drop(y);
+    /// interner.shrink_to_fit();
+    /// assert!(interner.validate(&handle));
+    ///
+    /// interner.remove(&42);
+    /// let z = interner.intern(42);
+    /// interner.shrink_to_fit();
+    /// assert!(!interner.validate(&handle));
+    /// assert!(interner.validate(&interner.tag(&z)));
+    /// ```
+    ///
+    /// Validating a handle created by a different interner panics in debug
+    /// builds:
+    /// ```rust,should_panic
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let mut other = RcInterner::new();
+    ///
+    /// let x = other.intern(42);
+    /// let handle = other.tag(&x);
+    ///
+    /// interner.validate(&handle);
+    /// # panic!("release builds do not check the interner");
+    /// ```
+    #[track_caller]
+    pub fn validate(&self, handle: &GenTaggedHandle<P>) -> bool {
+        #[cfg(debug_assertions)]
+        assert!(
+            handle.interner == self.id,
+            "generation-tagged handle was created by a different interner"
+        );
+
+        handle.generation == self.generation
+            || self.is_canonical(&handle.value)
+    }
+
+    /// Check whether a reference to an object is the reference to the object
+    /// interned in this interner
+    ///
+    /// Returns `false` if no equal object has been interned, or if an equal
+    /// object has been interned, but it is a different allocation, e.g.
+    /// because the reference was returned by a different interner.
+    ///
+    /// The reference can be a `P` or an opaque `RefCountHandle<P>`, see
+    /// `InternedRef`.
+    ///
+    /// # Panics
+    /// In debug builds, panics if `value` is an opaque handle returned by a
+    /// different interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let mut other = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = other.intern(42);
+    ///
+    /// assert!(interner.is_canonical(&x));
+    /// assert!(!interner.is_canonical(&y));
+    /// assert!(!interner.is_canonical(&Rc::new(1337)));
+    ///
+    /// let handle = interner.intern_opaque(42);
+    /// assert!(interner.is_canonical(&handle));
+    /// ```
+    ///
+    /// Checking an opaque handle returned by a different interner panics in
+    /// debug builds:
+    /// ```rust,should_panic
+    /// # use refcount_interner::RcInterner;
+    /// let interner = RcInterner::<i32>::new();
+    /// let mut other = RcInterner::new();
+    ///
+    /// let handle = other.intern_opaque(42);
+    ///
+    /// interner.is_canonical(&handle);
+    /// # panic!("release builds do not check the interner");
+    /// ```
+    #[track_caller]
+    pub fn is_canonical<R>(&self, value: &R) -> bool
+        where R: InternedRef<P>
+    {
+        #[cfg(debug_assertions)]
+        if let Some(interner_id) = value.interner_id() {
+            debug_assert_eq!(interner_id, self.id,
+                "opaque handle was returned by a different interner");
+        }
+
+        let value = value.pointer();
+        match self.set.get(value.as_ref()) {
+            Some(interned) => P::ptr_eq(interned, value),
+            None => false,
+        }
+    }
+
+    /// Check whether an object equal to the object of an opaque handle is
+    /// interned
+    ///
+    /// Unlike `is_canonical()`, this also returns `true` if the object of the
+    /// handle was removed and an equal object was interned again.
+    ///
+    /// # Panics
+    /// In debug builds, panics if the handle was returned by a different
+    /// interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let handle = interner.intern_opaque(42);
+    /// assert!(interner.contains_handle(&handle));
+    ///
+    /// interner.remove(&42);
+    /// assert!(!interner.contains_handle(&handle));
+    ///
+    /// interner.intern(42);
+    /// assert!(interner.contains_handle(&handle));
+    /// assert!(!interner.is_canonical(&handle));
+    /// ```
+    ///
+    /// Checking a handle returned by a different interner panics in debug
+    /// builds:
+    /// ```rust,should_panic
+    /// # use refcount_interner::RcInterner;
+    /// let interner = RcInterner::<i32>::new();
+    /// let mut other = RcInterner::new();
+    ///
+    /// let handle = other.intern_opaque(42);
+    ///
+    /// interner.contains_handle(&handle);
+    /// # panic!("release builds do not check the interner");
+    /// ```
+    #[track_caller]
+    pub fn contains_handle(&self, handle: &RefCountHandle<P>) -> bool {
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(handle.interner_id, self.id,
+            "opaque handle was returned by a different interner");
+
+        self.set.get(handle.as_ref()).is_some()
+    }
+
+    /// Get all handles in a slice that are not canonical.
+    ///
+    /// A handle is stale if `is_canonical()` returns `false` for it, e.g.
+    /// because its object was removed from the interner with `remove()` or
+    /// `clear()` and an equal object was interned again afterwards. The stale
+    /// handles are returned in the order they appear in the slice.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let kept = interner.intern(1);
+    /// let old = interner.intern(2);
+    ///
+    /// interner.remove(&2);
+    /// let new = interner.intern(2);
+    ///
+    /// let handles = vec![kept, old.clone(), new];
+    /// let stale = interner.find_stale(&handles);
+    ///
+    /// assert_eq!(stale.len(), 1);
+    /// assert!(Rc::ptr_eq(stale[0], &old));
+    /// ```
+    pub fn find_stale<'a>(&self, handles: &'a [P]) -> Vec<&'a P> {
+        handles.iter()
+            .filter(|&handle| !self.is_canonical(handle))
+            .collect()
+    }
+
+    /// Enable a bloom filter with the given number of bits in front of the
+    /// interned objects
+    ///
+    /// When a bloom filter is enabled, interning an object that is definitely
+    /// not yet interned skips the lookup in the internal storage. This speeds
+    /// up workloads where most interned objects are new. The bloom filter is
+    /// rebuilt by `shrink_to_fit()`, so deallocated objects do not cause false
+    /// positives afterwards.
+    ///
+    /// `bits` is only the initial size of the bloom filter. Whenever the
+    /// interned objects outgrow it, it is rebuilt with enough bits for twice
+    /// as many objects, so lookups stay fast as the interner grows.
+    ///
+    /// Calling this method again replaces the existing bloom filter.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// interner.enable_bloom_filter(1024);
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// assert_eq!(interner.bloom_filter_skips(), Some(1));
+    ///
+    /// let mut small = RcInterner::new();
+    /// small.enable_bloom_filter(64);
+    /// for i in 0..1000 {
+    ///     small.intern(i);
+    /// }
+    /// assert!(small.bloom_filter_skips().unwrap() > 900);
+    /// ```
+    pub fn enable_bloom_filter(&mut self, bits: usize) {
+        self.bloom = Some(BloomFilter::new(bits));
+        self.rebuild_bloom_filter();
+    }
+
+    /// Disable the bloom filter enabled by `enable_bloom_filter()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// interner.enable_bloom_filter(1024);
+    /// # let x = interner.intern(42);
+    ///
+    /// interner.disable_bloom_filter();
+    /// assert_eq!(interner.bloom_filter_skips(), None);
+    /// ```
+    pub fn disable_bloom_filter(&mut self) {
+        self.bloom = None;
+    }
+
+    /// Get the number of lookups the bloom filter has skipped
+    ///
+    /// If no bloom filter is enabled, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// interner.enable_bloom_filter(1024);
+    ///
+    /// for i in 0..10 {
+    ///     interner.intern(i);
+    /// }
+    ///
+    /// assert!(interner.bloom_filter_skips().unwrap() > 0);
+    /// ```
+    pub fn bloom_filter_skips(&self) -> Option<u64> {
+        self.bloom.as_ref().map(BloomFilter::skipped)
+    }
+
+    /// Deallocate all interned objects that are no longer referenced, without
+    /// shrinking the internal storage.
+    ///
+    /// This is cheaper than `shrink_to_fit()`, since the internal storage is
+    /// not reallocated, and can be used to reclaim the memory of unreferenced
+    /// objects in latency-sensitive code.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// for i in 0..100 {
+    ///     interner.intern(i);
+    /// }
+    /// let capacity = interner.capacity();
+    ///
+    /// interner.reap();
+    /// assert_eq!(interner.len(), 1);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert!(interner.capacity() < capacity);
+    /// ```
+    pub fn reap(&mut self) {
+        self.inserts_since_shrink = 0;
+        let len = self.set.len();
+        self.set.retain(|value| P::strong_count(value) > 1);
+        if self.set.len() != len {
+            self.generation += 1;
+        }
+
+        self.rebuild_bloom_filter();
+    }
+
+    /// Remove all interned objects that are no longer referenced, and return
+    /// them instead of deallocating them.
+    ///
+    /// Since the interner held the last reference to each removed object, each
+    /// returned handle is unique. The objects are deallocated when the returned
+    /// handles are dropped. Like `reap()`, this does not shrink the internal
+    /// storage.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// interner.intern(1337);
+    /// interner.intern(7);
+    ///
+    /// let mut dead = interner.drain_dead();
+    /// dead.sort();
+    ///
+    /// assert_eq!(dead, vec![Rc::new(7), Rc::new(1337)]);
+    /// assert!(dead.iter().all(|value| Rc::strong_count(value) == 1));
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn drain_dead(&mut self) -> Vec<P> {
+        self.inserts_since_shrink = 0;
+        let mut dead = Vec::new();
+        self.set.retain(|value| {
+            if P::strong_count(value) > 1 {
+                true
+            } else {
+                dead.push(value.clone());
+                false
+            }
+        });
+        if !dead.is_empty() {
+            self.generation += 1;
+        }
+
+        self.rebuild_bloom_filter();
+        dead
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    /// let z = y.clone();
+    ///
+    /// drop(x);
+    /// drop(y);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.try_intern(&42), None);
+    /// assert_eq!(interner.try_intern(&1337), Some(Rc::new(1337)));
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.reap();
+        if !self.capacity_frozen {
+            self.set.shrink_to_fit();
+        }
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage, keeping room for at least `min_capacity` objects.
+    ///
+    /// This avoids reallocating the internal storage right away when the
+    /// interner is expected to grow again. `min_capacity` is only a lower
+    /// bound: the capacity is never reduced below what the remaining objects
+    /// require, and the storage may keep more room than requested. Like
+    /// `shrink_to_fit()`, this does not reduce the capacity after
+    /// `freeze_capacity()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// for i in 0..1000 {
+    ///     interner.intern(i);
+    /// }
+    ///
+    /// interner.shrink_to(100);
+    /// assert_eq!(interner.len(), 1);
+    /// assert!(interner.capacity() >= 100);
+    /// assert!(interner.capacity() < 1000);
+    /// ```
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.reap();
+        if !self.capacity_frozen {
+            self.set.shrink_to(min_capacity);
+        }
+    }
+
+    /// Remove all interned objects for which `f` returns `false`, then shrink
+    /// the internal storage to fit the remaining objects.
+    ///
+    /// Handles to removed objects stay valid, but objects interned after this
+    /// call are no longer deduplicated against them. Like `shrink_to_fit()`,
+    /// this does not reduce the capacity after `freeze_capacity()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// for i in 0..1000 {
+    ///     interner.intern(i);
+    /// }
+    ///
+    /// interner.retain_and_compact(|&i| i % 10 == 0);
+    ///
+    /// assert_eq!(interner.len(), 100);
+    /// assert!(interner.contains(&990));
+    /// assert!(!interner.contains(&999));
+    /// assert!(interner.capacity() < 200);
+    /// ```
+    pub fn retain_and_compact<F>(&mut self, mut f: F)
+        where F: FnMut(&T) -> bool
+    {
+        let len = self.set.len();
+        self.set.retain(|value| f(value));
+        if self.set.len() != len {
+            self.generation += 1;
+        }
+
+        self.rebuild_bloom_filter();
+        if !self.capacity_frozen {
+            self.set.shrink_to_fit();
+        }
+    }
+
+    /// Remove all interned objects whose group key equals `target`.
+    ///
+    /// The group key of each interned object is computed with `key_fn`. Only
+    /// the references held by the interner are dropped: handles to removed
+    /// objects stay valid, but objects interned after this call are no longer
+    /// deduplicated against them.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// for name in &["alice/a", "alice/b", "bob/a", "carol/a"] {
+    ///     interner.intern_str(name);
+    /// }
+    ///
+    /// let tenant = |name: &str| name.split('/').next().unwrap().to_string();
+    /// interner.remove_group(tenant, &String::from("alice"));
+    ///
+    /// assert_eq!(interner.len(), 2);
+    /// assert!(!interner.contains("alice/a"));
+    /// assert!(!interner.contains("alice/b"));
+    /// assert!(interner.contains("bob/a"));
+    /// assert!(interner.contains("carol/a"));
+    /// ```
+    pub fn remove_group<K, F>(&mut self, key_fn: F, target: &K)
+        where K: PartialEq, F: Fn(&T) -> K
+    {
+        let len = self.set.len();
+        self.set.retain(|value| key_fn(value) != *target);
+        if self.set.len() != len {
+            self.generation += 1;
+        }
+
+        self.rebuild_bloom_filter();
+    }
+
+    /// Deallocate all interned objects that are no longer referenced and shrink
+    /// the internal storage to fit, returning an estimate of the number of
+    /// bytes freed.
+    ///
+    /// The estimate is the total size of the deallocated objects themselves,
+    /// such as the length of string slices, or the length of slices times the
+    /// size of their elements. Reference counts, allocator overhead and the
+    /// internal storage of the interner are not included.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut strings = RcInterner::new();
+    ///
+    /// let x = strings.intern_str("hello");
+    /// strings.intern_str("world!");
+    /// strings.intern_str("!");
+    ///
+    /// assert_eq!(strings.shrink_to_fit_bytes(), 7);
+    /// assert_eq!(strings.shrink_to_fit_bytes(), 0);
+    ///
+    /// let mut slices = RcInterner::new();
+    /// slices.intern_slice(&[1u32, 2, 3]);
+    /// assert_eq!(slices.shrink_to_fit_bytes(), 12);
+    /// ```
+    pub fn shrink_to_fit_bytes(&mut self) -> usize {
+        let freed = self.set.iter()
+            .filter(|value| P::strong_count(value) == 1)
+            .map(|value| mem::size_of_val(value.as_ref()))
+            .sum();
+
+        self.shrink_to_fit();
+        freed
+    }
+
+    /// Deallocate all interned objects that are no longer referenced, then
+    /// reserve space for at least `additional` more interned objects.
+    ///
+    /// Unlike `reserve()` on the internal storage alone, the space freed by the
+    /// deallocated objects is accounted for, so that the interner is not
+    /// grown needlessly before interning many objects at once.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let kept: Vec<_> = (0..10).map(|i| interner.intern(i)).collect();
+    /// for i in 10..100 {
+    ///     interner.intern(i);
+    /// }
+    ///
+    /// interner.reserve_accounting_stale(50);
+    ///
+    /// assert_eq!(interner.try_intern(&50), None);
+    /// assert_eq!(interner.len(), kept.len());
+    /// assert!(interner.capacity() >= kept.len() + 50);
+    /// ```
+    pub fn reserve_accounting_stale(&mut self, additional: usize) {
+        self.reap();
+        self.set.reserve(additional);
+    }
+
+    /// Remove an interned object from the interner
+    ///
+    /// Returns whether the object had been interned. Handles to the removed
+    /// object stay valid, but the object is no longer deduplicated against: if
+    /// a handle to it is still held, interning an equal object afterwards
+    /// allocates a new object that is not pointer-equal to the old one.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(interner.remove(&42));
+    /// assert!(!interner.remove(&42));
+    ///
+    /// let y = interner.intern(42);
+    /// assert_eq!(x, y);
+    /// assert!(!Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn remove(&mut self, t: &T) -> bool {
+        let removed = self.set.remove(t);
+        if removed {
+            self.generation += 1;
+        }
+        removed
+    }
+
+    /// Remove all interned objects from the interner, keeping its capacity.
+    ///
+    /// Handles to interned objects stay valid, but objects interned after this
+    /// call are no longer deduplicated against them.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::with_capacity(100);
+    ///
+    /// let x = interner.intern(42);
+    /// interner.clear();
+    ///
+    /// assert!(interner.is_empty());
+    /// assert!(interner.capacity() >= 100);
+    ///
+    /// let y = interner.intern(42);
+    /// assert_eq!(x, y);
+    /// assert!(!Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn clear(&mut self) {
+        if !self.set.is_empty() {
+            self.generation += 1;
+        }
+
+        self.set.clear();
+        self.rebuild_bloom_filter();
+    }
+
+    /// Stop `shrink_to_fit()` from reducing the capacity of the interner.
+    ///
+    /// After this call, `shrink_to_fit()` still deallocates interned objects
+    /// that are no longer referenced, but the internal storage keeps its
+    /// capacity. This avoids repeatedly shrinking and growing the storage in
+    /// long-lived interners with a steady workload.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::with_capacity(100);
+    /// interner.freeze_capacity();
+    ///
+    /// let x = interner.intern(42);
+    /// drop(x);
+    ///
+    /// let capacity = interner.capacity();
+    /// interner.shrink_to_fit();
+    ///
+    /// assert_eq!(interner.try_intern(&42), None);
+    /// assert_eq!(interner.capacity(), capacity);
+    /// ```
+    pub fn freeze_capacity(&mut self) {
+        self.capacity_frozen = true;
+    }
+
+    /// Automatically deallocate interned objects that are no longer referenced
+    /// after every `every_n_inserts` newly interned objects.
+    ///
+    /// Objects are only deallocated when a new object is interned, never when
+    /// an already interned object is looked up. The capacity of the interner
+    /// is not reduced automatically. Passing `None` disables automatic
+    /// deallocation, which is the default.
+    ///
+    /// # Panics
+    /// Panics if `every_n_inserts` is `Some(0)`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// interner.set_auto_shrink(Some(10));
+    ///
+    /// for i in 0..9 {
+    ///     interner.intern(i);
+    /// }
+    /// assert_eq!(interner.len(), 9);
+    ///
+    /// let x = interner.intern(9);
+    /// assert_eq!(interner.len(), 1);
+    ///
+    /// interner.set_auto_shrink(None);
+    /// for i in 10..100 {
+    ///     interner.intern(i);
+    /// }
+    /// assert_eq!(interner.len(), 91);
+    /// ```
+    pub fn set_auto_shrink(&mut self, every_n_inserts: Option<usize>) {
+        assert!(every_n_inserts != Some(0), "auto shrink interval is zero");
+        self.auto_shrink = every_n_inserts;
+        self.inserts_since_shrink = 0;
+    }
+
+    /// Create a checkpoint that the interner can be rolled back to
+    ///
+    /// While there are checkpoints that have been neither rolled back nor
+    /// committed, the interner records every newly interned object in a
+    /// journal. Checkpoints can be nested; they must be rolled back or
+    /// committed in reverse order of creation.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let outer = interner.checkpoint();
+    /// let x = interner.intern(1);
+    /// let inner = interner.checkpoint();
+    /// let y = interner.intern(2);
+    ///
+    /// interner.rollback(inner);
+    /// assert_eq!(interner.try_intern(&2), None);
+    ///
+    /// interner.rollback(outer);
+    /// assert_eq!(interner.try_intern(&1), None);
+    /// assert_eq!(*x, 1);
+    /// ```
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let journal = self.journal.get_or_insert_with(Vec::new);
+        self.checkpoints.push(journal.len());
+
+        Checkpoint {
+            position: journal.len(),
+            #[cfg(debug_assertions)]
+            interner: self.id,
+        }
+    }
+
+    /// Remove all objects interned after a checkpoint
+    ///
+    /// Only the references held by the interner are dropped; outstanding
+    /// references to the removed objects stay valid, but are no longer
+    /// canonical. Objects interned before the checkpoint are kept.
+    ///
+    /// Rolling back to a checkpoint created by a different interner panics in
+    /// debug builds.
+    ///
+    /// # Panics
+    /// Panics if an inner checkpoint is rolled back after an outer one, or
+    /// after an outer checkpoint has been committed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let checkpoint = interner.checkpoint();
+    /// let y = interner.intern(1337);
+    ///
+    /// interner.rollback(checkpoint);
+    /// assert_eq!(interner.try_intern(&42), Some(x));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// assert_eq!(*y, 1337);
+    /// ```
+    ///
+    /// Nested checkpoints may be created at the same position. Committing the
+    /// inner one keeps recording for the outer one:
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let outer = interner.checkpoint();
+    /// let inner = interner.checkpoint();
+    /// let x = interner.intern(1);
+    /// interner.commit(inner);
+    /// let y = interner.intern(2);
+    ///
+    /// interner.rollback(outer);
+    /// assert_eq!(interner.try_intern(&1), None);
+    /// assert_eq!(interner.try_intern(&2), None);
+    /// ```
+    ///
+    /// Rolling back checkpoints out of order panics:
+    /// ```rust,should_panic
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let outer = interner.checkpoint();
+    /// let x = interner.intern(42);
+    /// let inner = interner.checkpoint();
+    ///
+    /// interner.rollback(outer);
+    /// interner.rollback(inner);
+    /// ```
+    #[track_caller]
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        #[cfg(debug_assertions)]
+        assert!(
+            checkpoint.interner == self.id,
+            "checkpoint was created by a different interner"
+        );
+
+        assert!(
+            self.checkpoints.pop() == Some(checkpoint.position),
+            "checkpoint rolled back out of order or after commit"
+        );
+
+        let mut journal = self.journal.take().unwrap_or_default();
+
+        let mut removed = false;
+        for weak in journal.drain(checkpoint.position..).rev() {
+            if let Some(value) = P::upgrade(&weak) {
+                if self.is_canonical(&value) {
+                    removed |= self.set.remove(value.as_ref());
+                }
+            }
+        }
+
+        if !self.checkpoints.is_empty() {
+            self.journal = Some(journal);
+        }
+
+        if removed {
+            self.generation += 1;
+            self.rebuild_bloom_filter();
+        }
+    }
+
+    /// Keep all objects interned after a checkpoint
+    ///
+    /// Committing the outermost checkpoint stops recording newly interned
+    /// objects.
+    ///
+    /// # Panics
+    /// Panics if an outer checkpoint is committed before an inner one.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let checkpoint = interner.checkpoint();
+    /// let x = interner.intern(42);
+    ///
+    /// interner.commit(checkpoint);
+    /// assert_eq!(interner.try_intern(&42), Some(x));
+    /// ```
+    #[track_caller]
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        #[cfg(debug_assertions)]
+        assert!(
+            checkpoint.interner == self.id,
+            "checkpoint was created by a different interner"
+        );
+
+        assert!(
+            self.checkpoints.pop() == Some(checkpoint.position),
+            "checkpoint committed out of order or after rollback"
+        );
+
+        if self.checkpoints.is_empty() {
+            self.journal = None;
+        }
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Every interned object must be found again by lookup as the same
+    /// allocation, which also means that no two interned objects are equal,
+    /// and every interned object must pass the bloom filter, if enabled.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// interner.enable_bloom_filter(64);
+    ///
+    /// let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    /// let mut random = move |n: u64| {
+    ///     seed ^= seed << 13;
+    ///     seed ^= seed >> 7;
+    ///     seed ^= seed << 17;
+    ///     seed % n
+    /// };
+    ///
+    /// let mut handles = Vec::new();
+    /// for _ in 0..1000 {
+    ///     match random(3) {
+    ///         0 => handles.push(interner.intern(random(50))),
+    ///         1 if !handles.is_empty() => {
+    ///             let i = random(handles.len() as u64) as usize;
+    ///             handles.swap_remove(i);
+    ///         }
+    ///         _ => interner.shrink_to_fit(),
+    ///     }
+    ///     interner.check_invariants();
+    /// }
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        for value in self.set.iter() {
+            let found = self.set.get(value.as_ref())
+                .expect("interned object not found by lookup");
+            assert!(P::ptr_eq(found, value), "equal objects interned twice");
+
+            if let Some(bloom) = &self.bloom {
+                assert!(bloom.contains(value.as_ref()), "bloom filter miss");
+            }
+        }
+    }
+
+    fn lookup(&mut self, t: &T) -> Option<P> {
+        let skip = match &mut self.bloom {
+            Some(bloom) => !bloom.may_contain(t),
+            None => false,
+        };
+
+        let value = if skip { None } else { self.set.get(t).cloned() };
+
+        if value.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        if let Some(hit_rate) = &mut self.hit_rate {
+            hit_rate.record(value.is_some());
+        }
+
+        value
+    }
+
+    fn insert(&mut self, value: P) {
+        let mut saturated = false;
+        if let Some(bloom) = &mut self.bloom {
+            bloom.insert_probed(value.as_ref());
+            saturated = bloom.is_saturated();
+        }
+
+        if let Some(journal) = &mut self.journal {
+            journal.push(P::downgrade(&value));
+        }
+
+        self.set.insert(value);
+        if saturated {
+            self.rebuild_bloom_filter();
+        }
+
+        if let Some(every) = self.auto_shrink {
+            self.inserts_since_shrink += 1;
+            if self.inserts_since_shrink >= every {
+                self.reap();
+            }
+        }
+    }
+
+    fn displace(&mut self, value: &P) {
+        if self.displaced.len() == self.displaced.capacity() {
+            self.displaced.retain(|weak| P::upgrade(weak).is_some());
+        }
+
+        self.displaced.push(P::downgrade(value));
+    }
+
+    fn rebuild_bloom_filter(&mut self) {
+        if let Some(bloom) = &mut self.bloom {
+            bloom.clear(self.set.len());
+            for value in self.set.iter() {
+                bloom.insert(value.as_ref());
+            }
+        }
+    }
+}
+
+impl<P, T, B> RefCountInterner<P, T, B>
+    where P: RefCount<Target = T>, T: Sized + Hash + Eq, B: SetBackend<P>
+{
+    /// Intern an owned object
+    ///
+    /// If the object has already been interned, the passed object will be
+    /// dropped, and a reference to the already interned object will be
+    /// returned.
+    ///
+    /// If the object has not yet been interned, the passed object will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    ///
+    /// assert_eq!(*x, 42);
+    /// assert_ne!(x, y);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> P {
+        if let Some(value) = self.lookup(&t) {
+            value
+        } else {
+            let value = P::new(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern an owned object, reporting whether it was newly interned
+    ///
+    /// This behaves like `intern()`, but additionally returns `true` if the
+    /// object had not yet been interned and was inserted, and `false` if a
+    /// reference to an already interned object was returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let (x, inserted) = interner.intern_tracked(42);
+    /// assert!(inserted);
+    ///
+    /// let (y, inserted) = interner.intern_tracked(42);
+    /// assert!(!inserted);
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_tracked(&mut self, t: T) -> (P, bool) {
+        if let Some(value) = self.lookup(&t) {
+            (value, false)
+        } else {
+            let value = P::new(t);
+            self.insert(value.clone());
+            (value, true)
+        }
+    }
+
+    /// Get an already interned object, or intern an object produced by a
+    /// closure
+    ///
+    /// If the object has already been interned, a reference to the already
+    /// interned object will be returned, and `build` will not be called.
+    ///
+    /// If the object has not yet been interned, `build` will be called to
+    /// produce an owned object, which will be moved into a new `P`,
+    /// remembered for future calls to `intern()`, and returned. The owned
+    /// object must be equal to `key`.
+    ///
+    /// The key has the type of the interned objects, since the internal
+    /// storage can only be searched by `T`. For string slices and slices, see
+    /// `get_or_intern_str_with()` and `get_or_intern_boxed_with()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let mut calls = 0;
+    /// let x = interner.get_or_intern_with(&42, || {
+    ///     calls += 1;
+    ///     42
+    /// });
+    /// let y = interner.get_or_intern_with(&42, || unreachable!());
+    ///
+    /// assert_eq!(calls, 1);
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn get_or_intern_with<F>(&mut self, key: &T, build: F) -> P
+        where F: FnOnce() -> T
+    {
+        if let Some(value) = self.lookup(key) {
+            value
+        } else {
+            let value = P::new(build());
+            debug_assert!(*value == *key, "built value differs from key");
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern an owned object, reporting whether the internal storage grew
+    ///
+    /// This behaves like `intern()`, but additionally returns `true` if the
+    /// capacity of the interner increased during this call.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::with_capacity(1);
+    /// let (x, grew) = interner.intern_with_growth(1);
+    /// assert!(!grew);
+    ///
+    /// let mut handles = vec![x];
+    /// for i in 2.. {
+    ///     if interner.would_grow_on_insert() {
+    ///         break;
+    ///     }
+    ///     handles.push(interner.intern(i));
+    /// }
+    ///
+    /// let (y, grew) = interner.intern_with_growth(0);
+    /// assert!(grew);
+    /// ```
+    pub fn intern_with_growth(&mut self, t: T) -> (P, bool) {
+        let capacity = self.capacity();
+        let value = self.intern(t);
+        (value, self.capacity() > capacity)
+    }
+
+    /// Enable or disable keeping boxes dropped by `intern_boxed()` for reuse
+    ///
+    /// When enabled, calling `intern_boxed()` with an object that has already
+    /// been interned keeps the passed box in a free list instead of
+    /// deallocating it. The next call to `boxed()` moves its object into a box
+    /// from the free list, so hit-then-miss sequences of `boxed()` and
+    /// `intern_boxed()` allocate no new boxes. At most 64 boxes are kept.
+    ///
+    /// A box can only be reused for an object with the same memory layout,
+    /// which is why box reuse is only available for sized types. Disabling box
+    /// reuse deallocates all kept boxes. Box reuse is disabled by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::alloc::{GlobalAlloc, Layout, System};
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use refcount_interner::RcInterner;
+    /// struct Counter;
+    ///
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// unsafe impl GlobalAlloc for Counter {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+    ///         System.alloc(layout)
+    ///     }
+    ///
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         System.dealloc(ptr, layout)
+    ///     }
+    /// }
+    ///
+    /// #[global_allocator]
+    /// static COUNTER: Counter = Counter;
+    ///
+    /// fn allocations() -> usize {
+    ///     ALLOCATIONS.load(Ordering::SeqCst)
+    /// }
+    ///
+    /// fn main() {
+    ///     let mut interner = RcInterner::new().reuse_boxes(true);
+    ///     let x = interner.intern(42u64);
+    ///
+    ///     for i in 0..100 {
+    ///         let hit = interner.boxed(42);
+    ///         assert_eq!(interner.intern_boxed(hit), x);
+    ///
+    ///         let before = allocations();
+    ///         let miss = interner.boxed(i + 1000);
+    ///         assert_eq!(allocations(), before);
+    ///
+    ///         interner.intern_boxed(miss);
+    ///     }
+    ///
+    ///     let mut interner = interner.reuse_boxes(false);
+    ///     let hit = interner.boxed(42);
+    ///     interner.intern_boxed(hit);
+    ///
+    ///     let before = allocations();
+    ///     let miss = interner.boxed(0);
+    ///     assert_eq!(allocations(), before + 1);
+    /// }
+    /// ```
+    pub fn reuse_boxes(mut self, enabled: bool) -> RefCountInterner<P, T, B> {
+        self.free_boxes = if enabled { Some(Vec::new()) } else { None };
+        self
+    }
+
+    /// Move an object into a box, reusing a box kept by `intern_boxed()` if
+    /// possible
+    ///
+    /// If box reuse has not been enabled with `reuse_boxes()` or no box has
+    /// been kept, a new box will be allocated.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new().reuse_boxes(true);
+    /// let x = interner.intern(42);
+    ///
+    /// let y = interner.boxed(42);
+    /// let y_ptr: *const i32 = &*y;
+    /// interner.intern_boxed(y);
+    ///
+    /// let z = interner.boxed(1337);
+    /// assert_eq!(&*z as *const i32, y_ptr);
+    /// assert_eq!(*z, 1337);
+    /// ```
+    pub fn boxed(&mut self, t: T) -> Box<T> {
+        match self.free_boxes.as_mut().and_then(Vec::pop) {
+            Some(mut free_box) => {
+                *free_box = t;
+                free_box
+            }
+            None => Box::new(t),
+        }
+    }
+}
+
+impl<P, T, B> RefCountInterner<P, T, B>
+    where
+        P: RefCount<Target = T>,
+        T: Sized + Hash + Eq + Clone,
+        B: SetBackend<P>,
+{
+    /// Intern a borrowed object, cloning if it has not yet been interned
+    ///
+    /// If the object has already been interned, a reference to the already
+    /// interned object will be returned.
+    ///
+    /// If the object has not yet been interned, the passed object will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = 42;
+    /// let y = interner.intern_cloned(&x);
+    ///
+    /// assert_eq!(x, *y);
+    /// ```
+    pub fn intern_cloned(&mut self, t: &T) -> P {
+        if let Some(value) = self.lookup(t) {
+            value
+        } else {
+            let value = P::new(t.clone());
+            self.insert(value.clone());
+            value
+        }
+    }
+}
+
+impl<P, T, B> RefCountInterner<P, [T], B>
+    where
+        P: RefCount<Target = [T]> + for<'a> From<&'a [T]>,
+        T: Sized + Hash + Eq + Clone,
+        B: SetBackend<P>,
+{
+    /// Intern a slice object
+    ///
+    /// This method can be used to intern slices without boxing them.
+    ///
+    /// If the slice has already been interned, a reference to the already
+    /// interned slice will be returned.
+    ///
+    /// If the slice has not yet been interned, the passed object will be
+    /// cloned into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_slice(&[1, 2, 3]);
+    ///
+    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
+    /// ```
+    pub fn intern_slice(&mut self, t: &[T]) -> P {
+        if let Some(value) = self.lookup(t) {
+            value
+        } else {
+            let value = P::from(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern an owned vector
+    ///
+    /// If the slice behind the vector has already been interned, a reference
+    /// to the already / interned slice will be returned.
+    ///
+    /// If the slice has not yet been interned, the passed vector will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    ///
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let v = vec![1, 2, 3];
+    /// let x = interner.intern_vec(v);
+    ///
+    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
+    /// ```
+    pub fn intern_vec(&mut self, t: Vec<T>) -> P {
+        self.intern_boxed(t.into_boxed_slice())
+    }
+
+    /// Intern a borrowed or owned slice
+    ///
+    /// Borrowed slices are interned with `intern_slice()`, so they are only
+    /// cloned if they have not yet been interned. Owned vectors are interned
+    /// with `intern_vec()`, so their elements are moved instead of cloned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::borrow::Cow;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner: RcInterner<[u32]> = RcInterner::new();
+    ///
+    /// let x = interner.intern_cow(Cow::Borrowed(&[1, 2, 3][..]));
+    /// let y = interner.intern_cow(Cow::Owned(vec![1, 2, 3]));
+    ///
+    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_cow(&mut self, t: Cow<'_, [T]>) -> P {
+        match t {
+            Cow::Borrowed(t) => self.intern_slice(t),
+            Cow::Owned(t) => self.intern_vec(t),
+        }
+    }
+
+    /// Intern an owned vector without shrinking it first
+    ///
+    /// If the slice behind the vector has already been interned, the vector
+    /// will be dropped, and a reference to the already interned slice will be
+    /// returned.
+    ///
+    /// If the slice has not yet been interned, its elements will be moved
+    /// directly into a new allocation, remembered for future calls
+    /// to `intern()`, and returned.
+    ///
+    /// Unlike `intern_vec()`, this never shrinks the vector to fit before
+    /// moving its elements, which may reallocate and copy the elements an
+    /// additional time if the vector has spare capacity. The elements are
+    /// copied exactly once, into the new allocation.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::alloc::{GlobalAlloc, Layout, System};
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # struct Counting;
+    /// # static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+    /// # unsafe impl GlobalAlloc for Counting {
+    /// #     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    /// #         ALLOCS.fetch_add(1, Ordering::SeqCst);
+    /// #         System.alloc(layout)
+    /// #     }
+    /// #     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    /// #         System.dealloc(ptr, layout)
+    /// #     }
+    /// #     unsafe fn realloc(&self, ptr: *mut u8, l: Layout, size: usize)
+    /// #         -> *mut u8
+    /// #     {
+    /// #         ALLOCS.fetch_add(1, Ordering::SeqCst);
+    /// #         System.realloc(ptr, l, size)
+    /// #     }
+    /// # }
+    /// # #[global_allocator]
+    /// # static GLOBAL: Counting = Counting;
+    /// # fn main() {
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// interner.preallocate_next();
+    ///
+    /// let mut v = Vec::with_capacity(1024);
+    /// v.extend_from_slice(&[1, 2, 3]);
+    ///
+    /// let before = ALLOCS.load(Ordering::SeqCst);
+    /// let x = interner.intern_vec_exact(v);
+    /// assert_eq!(ALLOCS.load(Ordering::SeqCst) - before, 1);
+    ///
+    /// assert_eq!(x.as_ref(), &[1, 2, 3]);
+    /// # }
+    /// ```
+    pub fn intern_vec_exact(&mut self, t: Vec<T>) -> P
+        where P: From<Vec<T>>
+    {
+        if let Some(value) = self.lookup(&t) {
+            value
+        } else {
+            let value = P::from(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+}
+
+impl<P> RefCountInterner<P, str>
+    where P: RefCount<Target = str> + Hash + Eq + for<'a> From<&'a str>
+{
+    /// Create an interner from a sorted slice of unique strings
+    ///
+    /// This is a fast path for bootstrapping an interner from a vocabulary
+    /// that has already been deduplicated, e.g. one that has been persisted.
+    /// Space for all strings is reserved once, and each string is inserted
+    /// without first looking it up.
+    ///
+    /// The strings should be sorted and unique. Since equal strings are
+    /// adjacent in a sorted slice, duplicates in a sorted slice are skipped by
+    /// comparing each string with the previous one. If the slice is not sorted,
+    /// the interner is still valid and deduplicated, but may reserve more space
+    /// than necessary.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let vocabulary: Vec<String> = ["apple", "banana", "cherry"].iter()
+    ///     .map(|s| s.to_string())
+    ///     .collect();
+    ///
+    /// let mut interner = RcInterner::from_sorted_unique(&vocabulary);
+    /// assert_eq!(interner.len(), vocabulary.len());
+    ///
+    /// let x = interner.try_intern("banana").unwrap();
+    /// assert!(Rc::ptr_eq(&x, &interner.intern_str("banana")));
+    /// assert_eq!(interner.try_intern("durian"), None);
+    /// ```
+    pub fn from_sorted_unique(items: &[String]) -> RefCountInterner<P, str> {
+        let mut interner = RefCountInterner::with_capacity(items.len());
+        let mut previous: Option<&str> = None;
+        for item in items {
+            if previous != Some(item.as_str()) {
+                interner.set.insert(P::from(item.as_str()));
+                previous = Some(item);
+            }
+        }
+        interner
+    }
+}
+
+impl<P, B> RefCountInterner<P, str, B>
+    where P: RefCount<Target = str> + for<'a> From<&'a str>, B: SetBackend<P>
+{
+    /// Intern a string slice
+    ///
+    /// This method can be used to intern string slices without boxing them.
+    ///
+    /// If the string slice has already been interned, a reference to the
+    /// already interned string slice will be returned.
+    ///
+    /// If the string slice has not yet been interned, the passed object will be
+    /// cloned into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_str("hello");
+    ///
+    /// assert_eq!(x.as_ref(), "hello");
+    /// ```
+    pub fn intern_str(&mut self, t: &str) -> P {
+        if let Some(value) = self.lookup(t) {
+            value
+        } else {
+            let value = P::from(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern an owned string
+    ///
+    /// If the string has already been interned, a reference to the already
+    /// interned string slice will be returned.
+    ///
+    /// If the string has not yet been interned, the passed string will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    ///
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let s = String::from("hello");
+    /// let x = interner.intern_string(s);
+    ///
+    /// assert_eq!(x.as_ref(), "hello");
+    /// ```
+    pub fn intern_string(&mut self, t: String) -> P {
+        self.intern_boxed(t.into_boxed_str())
+    }
+
+    /// Intern a borrowed or owned string
+    ///
+    /// Borrowed strings are interned with `intern_str()`, so they are only
+    /// cloned if they have not yet been interned. Owned strings are interned
+    /// with `intern_string()`, so they are moved instead of cloned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::borrow::Cow;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner: RcInterner<str> = RcInterner::new();
+    ///
+    /// let x = interner.intern_cow(Cow::Borrowed("hello"));
+    /// let y = interner.intern_cow(Cow::Owned(String::from("hello")));
+    ///
+    /// assert_eq!(x.as_ref(), "hello");
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_cow(&mut self, t: Cow<'_, str>) -> P {
+        match t {
+            Cow::Borrowed(t) => self.intern_str(t),
+            Cow::Owned(t) => self.intern_string(t),
+        }
+    }
+
+    /// Replace every string slice handle in a slice with the handle of the
+    /// interned string slice
+    ///
+    /// Each handle is interned with `intern_shared()`, so string slices that
+    /// have not yet been interned are adopted without copying. Afterwards,
+    /// all equal string slices in the slice share one allocation.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let mut column: Vec<Rc<str>> = ["a", "b", "a", "a", "b"].iter()
+    ///     .map(|&s| Rc::from(s))
+    ///     .collect();
+    /// interner.canonicalize_slice(&mut column);
+    ///
+    /// assert!(Rc::ptr_eq(&column[0], &column[2]));
+    /// assert!(Rc::ptr_eq(&column[0], &column[3]));
+    /// assert!(Rc::ptr_eq(&column[1], &column[4]));
+    /// assert_eq!(interner.len(), 2);
+    /// ```
+    pub fn canonicalize_slice(&mut self, slice: &mut [P]) {
+        for value in slice {
+            *value = self.intern_shared(value.clone());
+        }
+    }
+
+    /// Intern the entire contents of a reader as a string
+    ///
+    /// The reader is always read to the end, even if an equal string has
+    /// already been interned. If reading fails or the contents are not valid
+    /// UTF-8, an error will be returned.
+    ///
+    /// If the string has already been interned, a reference to the already
+    /// interned string slice will be returned.
+    ///
+    /// If the string has not yet been interned, the read string will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::io::Cursor;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_from_reader(Cursor::new("hello"))?;
+    /// let y = interner.intern_from_reader(Cursor::new(b"hello"))?;
+    /// assert!(Rc::ptr_eq(&x, &y));
+    ///
+    /// assert!(interner.intern_from_reader(Cursor::new(b"\xff")).is_err());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn intern_from_reader<R: Read>(&mut self, mut r: R)
+        -> io::Result<P>
+    {
+        let mut s = String::new();
+        r.read_to_string(&mut s)?;
+        Ok(self.intern_string(s))
+    }
+
+    /// Intern a string slice, taking the contents of a caller-provided buffer
+    /// if the string slice has not yet been interned
+    ///
+    /// The buffer `buf` is expected to contain the same string as `s`.
+    ///
+    /// If the string slice has already been interned, a reference to the
+    /// already interned string slice will be returned, and `buf` is left
+    /// untouched.
+    ///
+    /// If the string slice has not yet been interned and `buf` is equal to
+    /// `s`, the contents of `buf` will be taken and moved into a new `P`,
+    /// leaving `buf` empty. If `buf` is not equal to `s`, `s` is cloned
+    /// instead, as in `intern_str()`, and `buf` is left untouched.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let mut buf = String::from("hello");
+    /// let x = interner.intern_str_reusing("hello", &mut buf);
+    /// assert_eq!(x.as_ref(), "hello");
+    /// assert!(buf.is_empty());
+    ///
+    /// let mut buf = String::from("hello");
+    /// let y = interner.intern_str_reusing("hello", &mut buf);
+    /// assert_eq!(buf, "hello");
+    ///
+    /// let metrics = interner.metrics();
+    /// assert_eq!((metrics.hits(), metrics.misses()), (1, 1));
+    /// ```
+    pub fn intern_str_reusing(&mut self, s: &str, buf: &mut String) -> P {
+        if let Some(value) = self.lookup(s) {
+            return value;
+        }
+
+        let value = if buf == s {
+            P::from_box(mem::take(buf).into_boxed_str())
+        } else {
+            P::from(s)
+        };
+        self.insert(value.clone());
+        value
+    }
+
+    /// Intern a string slice, lazily building the owned string if it has not
+    /// yet been interned
+    ///
+    /// If the string slice has already been interned, a reference to the
+    /// already interned string slice will be returned, and `build` will not be
+    /// called.
+    ///
+    /// If the string slice has not yet been interned, `build` will be called
+    /// to produce a boxed string, which will be moved into a new `P`,
+    /// remembered for future calls to `intern()`, and returned. The boxed
+    /// string must be equal to `key`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let mut calls = 0;
+    /// let x = interner.get_or_intern_str_with("hello", || {
+    ///     calls += 1;
+    ///     Box::from("hello")
+    /// });
+    /// let y = interner.get_or_intern_str_with("hello", || unreachable!());
+    ///
+    /// assert_eq!(calls, 1);
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn get_or_intern_str_with<F>(&mut self, key: &str, build: F) -> P
+        where F: FnOnce() -> Box<str>
+    {
+        self.get_or_intern_boxed_with(key, build)
+    }
+
+    /// Re-allocate interned string slices that are only referenced by the
+    /// interner, improving their locality
+    ///
+    /// After a lot of interning and deallocation, the allocations of interned
+    /// string slices are scattered across the heap. This copies the contents
+    /// of all interned string slices that are not referenced outside of the
+    /// interner into new allocations, made back to back so that the allocator
+    /// can place them next to each other, and deallocates the old ones. Each
+    /// `Rc<str>` or `Arc<str>` owns its allocation, so they cannot be placed
+    /// in a shared arena.
+    ///
+    /// Interned string slices that are still referenced, either by `Rc<str>`
+    /// or by `Weak<str>` handles, are not moved. Since moved string slices
+    /// were not referenced, no handle observes the change, but their addresses
+    /// are different afterwards.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_str("hello");
+    /// let y = interner.intern_str("world");
+    /// let (x_ptr, y_ptr) = (Rc::as_ptr(&x), Rc::as_ptr(&y));
+    /// drop(x);
+    ///
+    /// interner.compact();
+    ///
+    /// let x = interner.try_intern("hello").unwrap();
+    /// assert_eq!(x.as_ref(), "hello");
+    /// assert_ne!(Rc::as_ptr(&x), x_ptr);
+    /// assert_eq!(Rc::as_ptr(&interner.intern_str("world")), y_ptr);
+    /// ```
+    pub fn compact(&mut self) {
+        let unique: Vec<P> = self.set.iter()
+            .filter(|value| {
+                P::strong_count(value) == 1 && P::weak_count(value) == 0
+            })
+            .cloned()
+            .collect();
+
+        let moved: Vec<P> = unique.iter()
+            .map(|value| P::from(value.as_ref()))
+            .collect();
+
+        for value in moved {
+            self.set.replace(value);
+        }
+    }
+}
+
+impl<P, B> RefCountInterner<P, OsStr, B>
+    where
+        P: RefCount<Target = OsStr> + for<'a> From<&'a OsStr>,
+        B: SetBackend<P>,
+{
+    /// Intern an OS string slice
+    ///
+    /// This method can be used to intern OS string slices without boxing them,
+    /// and without a lossy conversion to UTF-8.
+    ///
+    /// If the OS string slice has already been interned, a reference to the
+    /// already interned OS string slice will be returned.
+    ///
+    /// If the OS string slice has not yet been interned, the passed object will
+    /// be cloned into a new `P`, remembered for future calls to
+    /// `intern()`, and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::ffi::OsStr;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_os_str(OsStr::new("hello"));
+    ///
+    /// assert_eq!(x.as_ref(), OsStr::new("hello"));
+    /// ```
+    pub fn intern_os_str(&mut self, t: &OsStr) -> P {
+        if let Some(value) = self.lookup(t) {
+            value
+        } else {
+            let value = P::from(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern an owned OS string
+    ///
+    /// If the OS string has already been interned, a reference to the already
+    /// interned OS string slice will be returned.
+    ///
+    /// If the OS string has not yet been interned, the passed OS string will be
+    /// moved into a new `P`, remembered for future calls to `intern()`,
+    /// and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::ffi::{OsStr, OsString};
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_os_str(OsStr::new("hello"));
+    /// let y = interner.intern_os_string(OsString::from("hello"));
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_os_string(&mut self, t: OsString) -> P {
+        self.intern_boxed(t.into_boxed_os_str())
+    }
+}
+
+impl<P, B> RefCountInterner<P, Path, B>
+    where
+        P: RefCount<Target = Path> + for<'a> From<&'a Path>,
+        B: SetBackend<P>,
+{
+    /// Intern a path slice
+    ///
+    /// This method can be used to intern path slices without boxing them.
+    ///
+    /// If the path slice has already been interned, a reference to the
+    /// already interned path slice will be returned.
+    ///
+    /// If the path slice has not yet been interned, the passed object will be
+    /// cloned into a new `P`, remembered for future calls to `intern()`,
+    /// and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::path::Path;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_path(Path::new("/usr/lib"));
+    /// let y = interner.intern_path(Path::new("/usr/lib"));
+    ///
+    /// assert_eq!(x.as_ref(), Path::new("/usr/lib"));
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_path(&mut self, t: &Path) -> P {
+        if let Some(value) = self.lookup(t) {
+            value
+        } else {
+            let value = P::from(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern an owned path
+    ///
+    /// If the path has already been interned, a reference to the already
+    /// interned path slice will be returned.
+    ///
+    /// If the path has not yet been interned, the passed path will be moved
+    /// into a new `P`, remembered for future calls to `intern()`, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::path::{Path, PathBuf};
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_path_buf(PathBuf::from("/usr").join("lib"));
+    /// let y = interner.intern_path_buf(PathBuf::from("/usr/lib"));
+    ///
+    /// assert_eq!(x.as_ref(), Path::new("/usr/lib"));
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_path_buf(&mut self, t: PathBuf) -> P {
+        self.intern_boxed(t.into_boxed_path())
+    }
+}
+
+impl<P, B> RefCountInterner<P, CStr, B>
+    where
+        P: RefCount<Target = CStr> + for<'a> From<&'a CStr>,
+        B: SetBackend<P>,
+{
+    /// Intern a C string slice
+    ///
+    /// This method can be used to intern C string slices without boxing them.
+    ///
+    /// If the C string slice has already been interned, a reference to the
+    /// already interned C string slice will be returned.
+    ///
+    /// If the C string slice has not yet been interned, the passed object will
+    /// be cloned into a new `P`, remembered for future calls to
+    /// `intern()`, and returned.
+    ///
+    /// Since only one copy of each C string is kept alive, the pointer returned
+    /// by `as_ptr()` is the same for all handles to equal C strings.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::ffi::CStr;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let s = CStr::from_bytes_with_nul(b"hello\0")?;
+    /// let x = interner.intern_cstr(s);
+    /// let y = interner.intern_cstr(s);
+    ///
+    /// assert_eq!(x.as_ref(), s);
+    /// assert_eq!(x.as_ptr(), y.as_ptr());
+    /// # Ok::<(), std::ffi::FromBytesWithNulError>(())
+    /// ```
+    pub fn intern_cstr(&mut self, t: &CStr) -> P {
+        if let Some(value) = self.lookup(t) {
+            value
+        } else {
+            let value = P::from(t);
+            self.insert(value.clone());
+            value
+        }
+    }
+
+    /// Intern an owned C string
+    ///
+    /// If the C string has already been interned, a reference to the already
+    /// interned C string slice will be returned.
+    ///
+    /// If the C string has not yet been interned, the passed C string will be
+    /// moved into a new `P`, remembered for future calls to `intern()`,
+    /// and returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::ffi::CString;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    ///
+    /// let x = interner.intern_cstring(CString::new("hello")?);
+    /// let y = interner.intern_cstring(CString::new("hello")?);
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// # Ok::<(), std::ffi::NulError>(())
+    /// ```
+    pub fn intern_cstring(&mut self, t: CString) -> P {
+        self.intern_boxed(t.into_boxed_c_str())
+    }
+}
+
+impl<P, T, S> RefCountInterner<P, T, HashSet<P, S>>
+    where
+        P: RefCount<Target = T> + Hash + Eq,
+        T: ?Sized + Hash + Eq,
+        S: BuildHasher,
+{
+    /// Create a new, empty interner hashing interned objects with `hasher`
+    ///
+    /// The interned objects are stored in a `HashSet<P, S>`, which is the
+    /// second type parameter of the interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::collections::HashSet;
+    /// # use std::hash::{BuildHasherDefault, Hasher};
+    /// # use refcount_interner::RcInterner;
+    /// #[derive(Default)]
+    /// struct FnvHasher(u64);
+    ///
+    /// impl Hasher for FnvHasher {
+    ///     fn finish(&self) -> u64 {
+    ///         self.0
+    ///     }
+    ///
+    ///     fn write(&mut self, bytes: &[u8]) {
+    ///         for &byte in bytes {
+    ///             self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// type Fnv = BuildHasherDefault<FnvHasher>;
+    /// let mut interner: RcInterner<str, HashSet<_, Fnv>> =
+    ///     RcInterner::with_hasher(Fnv::default());
+    ///
+    /// let x = interner.intern_str("hello");
+    /// assert_eq!(interner.try_intern("hello"), Some(x));
+    /// ```
+    pub fn with_hasher(hasher: S) -> RefCountInterner<P, T, HashSet<P, S>> {
+        RefCountInterner::from_set(HashSet::with_hasher(hasher))
+    }
+
+    /// Create a new, empty interner with space for at least `capacity`
+    /// interned objects, hashing interned objects with `hasher`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::collections::hash_map::RandomState;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner =
+    ///     RcInterner::with_capacity_and_hasher(100, RandomState::new());
+    ///
+    /// assert!(interner.capacity() >= 100);
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S)
+        -> RefCountInterner<P, T, HashSet<P, S>>
+    {
+        let set = HashSet::with_capacity_and_hasher(capacity, hasher);
+        RefCountInterner::from_set(set)
+    }
+
+    /// Estimate how many interned objects collide with another interned
+    /// object in the hash table of the interner.
+    ///
+    /// The objects are hashed with the hasher of the interner, and the hashes
+    /// are truncated to 32 bits. The number of objects whose truncated hash
+    /// equals that of an object counted before is returned. A well-distributed
+    /// hasher gives close to zero collisions unless very many objects are
+    /// interned, while a poorly distributed hasher gives many collisions.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::collections::HashSet;
+    /// # use std::hash::{BuildHasherDefault, Hasher};
+    /// # use refcount_interner::RcInterner;
+    /// #[derive(Default)]
+    /// struct ConstantHasher;
+    ///
+    /// impl Hasher for ConstantHasher {
+    ///     fn finish(&self) -> u64 {
+    ///         0
+    ///     }
+    ///
+    ///     fn write(&mut self, _bytes: &[u8]) {}
+    /// }
+    ///
+    /// let constant = BuildHasherDefault::<ConstantHasher>::default();
+    /// let set = HashSet::with_hasher(constant);
+    /// let mut bad = RcInterner::with_backend(set);
+    /// let mut good = RcInterner::new();
+    ///
+    /// for i in 0..100 {
+    ///     bad.intern(i);
+    ///     good.intern(i);
+    /// }
+    ///
+    /// assert_eq!(bad.estimate_collisions(), 99);
+    /// assert!(good.estimate_collisions() <= 1);
+    /// ```
+    pub fn estimate_collisions(&self) -> usize {
+        let mut buckets = HashSet::with_capacity(self.set.len());
+        self.set.iter()
+            .filter(|value| {
+                let hash = self.set.hasher().hash_one(value.as_ref());
+                !buckets.insert(hash as u32)
+            })
+            .count()
+    }
+}
+
+impl<P, T, B> Interner<T> for RefCountInterner<P, T, B>
+    where P: RefCount<Target = T>, T: ?Sized + Hash + Eq, B: SetBackend<P>
+{
+    type Handle = P;
+
+    fn try_intern(&self, t: &T) -> Option<P> {
+        RefCountInterner::try_intern(self, t)
+    }
+
+    fn intern_boxed(&mut self, t: Box<T>) -> P {
+        RefCountInterner::intern_boxed(self, t)
+    }
+
+    fn intern(&mut self, t: T) -> P where T: Sized {
+        RefCountInterner::intern(self, t)
+    }
+
+    fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.set.capacity()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.set.reserve(additional);
+    }
+
+    fn clear(&mut self) {
+        RefCountInterner::clear(self)
+    }
+
+    fn shrink_to_fit(&mut self) {
+        RefCountInterner::shrink_to_fit(self)
+    }
+}
+
+/// Collect objects into a new interner
+///
+/// The interner contains one interned object per distinct object of the
+/// iterator, and is presized from the lower bound of the iterator's size hint.
+///
+/// Since the handles to the interned objects are not returned, no interned
+/// object is referenced outside of the interner, and the next call to
+/// `shrink_to_fit()` deallocates all of them. Callers that need the handles
+/// should call `intern()` in a loop instead.
+///
+/// # Example
+/// ```rust
+/// # use refcount_interner::RcInterner;
+/// let interner: RcInterner<u32> = (0..1000).collect();
+///
+/// let presized = RcInterner::<u32>::with_capacity(1000);
+/// assert_eq!(interner.capacity(), presized.capacity());
+/// assert_eq!(interner.try_intern(&42).as_deref(), Some(&42));
+///
+/// let interner: RcInterner<u32> = vec![1, 2, 1, 3, 2].into_iter().collect();
+/// assert_eq!(interner.len(), 3);
+/// ```
+impl<P, T, B> FromIterator<T> for RefCountInterner<P, T, B>
+    where
+        P: RefCount<Target = T>,
+        T: Sized + Hash + Eq,
+        B: SetBackend<P> + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I)
+        -> RefCountInterner<P, T, B>
+    {
+        let mut interner = RefCountInterner::default();
+        interner.extend(iter);
+        interner
+    }
+}
+
+/// Intern all objects of an iterator
+///
+/// Space for the lower bound of the iterator's size hint is reserved before
+/// interning.
+///
+/// # Example
+/// ```rust
+/// # use refcount_interner::RcInterner;
+/// let mut interner = RcInterner::new();
+/// interner.extend(0..1000);
+///
+/// let presized = RcInterner::<u32>::with_capacity(1000);
+/// assert_eq!(interner.capacity(), presized.capacity());
+///
+/// interner.extend(vec![2000, 2001, 2000]);
+/// assert_eq!(interner.len(), 1002);
+/// ```
+impl<P, T, B> Extend<T> for RefCountInterner<P, T, B>
+    where P: RefCount<Target = T>, T: Sized + Hash + Eq, B: SetBackend<P>
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.set.reserve(iter.size_hint().0);
+        for t in iter {
+            self.intern(t);
+        }
+    }
+}
+
+/// Intern all boxed slices of an iterator
+///
+/// See `Extend<T>`.
+///
+/// # Example
+/// ```rust
+/// # use refcount_interner::RcInterner;
+/// let mut interner = RcInterner::new();
+///
+/// let slices: Vec<Box<[u32]>> = vec![
+///     Box::new([1, 2]),
+///     Box::new([3]),
+///     Box::new([1, 2]),
+/// ];
+/// interner.extend(slices);
+///
+/// assert_eq!(interner.len(), 2);
+/// assert!(interner.try_intern(&[1, 2][..]).is_some());
+/// ```
+impl<P, T, B> Extend<Box<[T]>> for RefCountInterner<P, [T], B>
+    where P: RefCount<Target = [T]>, T: Hash + Eq, B: SetBackend<P>
+{
+    fn extend<I: IntoIterator<Item = Box<[T]>>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.set.reserve(iter.size_hint().0);
+        for t in iter {
+            self.intern_boxed(t);
+        }
+    }
+}
+
+/// Intern all strings of an iterator
+///
+/// See `Extend<T>`.
+///
+/// # Example
+/// ```rust
+/// # use refcount_interner::RcInterner;
+/// let mut interner = RcInterner::new();
+///
+/// let words = "to be or not to be".split(' ').map(String::from);
+/// interner.extend(words);
+///
+/// assert_eq!(interner.len(), 4);
+/// assert!(interner.try_intern("not").is_some());
+/// ```
+impl<P, B> Extend<String> for RefCountInterner<P, str, B>
+    where P: RefCount<Target = str> + for<'a> From<&'a str>, B: SetBackend<P>
+{
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.set.reserve(iter.size_hint().0);
+        for t in iter {
+            self.intern_string(t);
+        }
+    }
+}
+
+/// A read-only interner produced by `RefCountInterner::freeze()`
+///
+/// A frozen interner only supports looking up already interned objects by
+/// reference, so no lookup allocates or modifies reference counts. Use
+/// `thaw()` to convert it back into a `RefCountInterner`.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::RcInterner;
+///
+/// let mut interner = RcInterner::new();
+/// let x = interner.intern(42);
+///
+/// let frozen = interner.freeze();
+/// assert!(Rc::ptr_eq(&x, frozen.get(&42).unwrap()));
+/// assert_eq!(frozen.get(&1337), None);
+/// ```
+#[derive(Debug)]
+pub struct FrozenRefCountInterner<P, T = <P as Deref>::Target, B = HashSet<P>>(
+    RefCountInterner<P, T, B>,
+)
+    where P: RefCount, T: ?Sized;
+
+impl<P, T, B> RefCountInterner<P, T, B>
+    where P: RefCount<Target = T>, T: ?Sized + Hash + Eq, B: SetBackend<P>
+{
+    /// Freeze the interner, producing a read-only interner
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let x = interner.intern(42);
+    ///
+    /// let frozen = interner.freeze();
+    /// assert_eq!(frozen.get(&42), Some(&x));
+    /// ```
+    pub fn freeze(self) -> FrozenRefCountInterner<P, T, B> {
+        FrozenRefCountInterner(self)
+    }
+
+    /// Finalize the interner, producing a read-only interner using a perfect
+    /// hash function for lookups
+    ///
+    /// See `PerfectHashInterner`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let x = interner.intern(42);
+    ///
+    /// let finalized = interner.finalize();
+    /// assert_eq!(finalized.get(&42), Some(&x));
+    /// assert_eq!(finalized.get(&1337), None);
+    /// ```
+    pub fn finalize(self) -> PerfectHashInterner<P> {
+        PerfectHashInterner::new(self.set.iter().cloned().collect())
+    }
+}
+
+impl<P, T, B> FrozenRefCountInterner<P, T, B>
+    where P: RefCount<Target = T>, T: ?Sized + Hash + Eq, B: SetBackend<P>
+{
+    /// Get a reference to an already interned object.
+    ///
+    /// If the object has been interned, an option containing a reference to
+    /// the interned object will be returned.
+    ///
+    /// If the object has not been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let x = interner.intern(42);
+    ///
+    /// let frozen = interner.freeze();
+    /// assert_eq!(frozen.get(&42), Some(&Rc::new(42)));
+    /// assert_eq!(frozen.get(&1337), None);
+    /// ```
+    pub fn get(&self, t: &T) -> Option<&P> {
+        self.0.set.get(t)
+    }
+
+    /// Iterate over all interned objects in arbitrary order.
+    ///
+    /// The iterator is the one of the backend, so iterating does not
+    /// allocate.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let x = interner.intern(42);
+    ///
+    /// let frozen = interner.freeze();
+    /// assert_eq!(frozen.iter().collect::<Vec<_>>(), vec![&x]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &P> + '_ {
+        self.0.set.iter()
+    }
+
+    /// Thaw the interner, converting it back into a `RefCountInterner`
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::RcInterner;
+    /// let mut interner = RcInterner::new();
+    /// let x = interner.intern(42);
+    ///
+    /// let mut interner = interner.freeze().thaw();
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn thaw(self) -> RefCountInterner<P, T, B> {
+        self.0
+    }
+}
+
+/// Get the address of the allocation of a handle, ignoring metadata
+fn address<P: RefCount>(value: &P) -> *const () {
+    P::as_ptr(value) as *const ()
+}
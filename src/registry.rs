@@ -0,0 +1,142 @@
+use std::rc::Rc;
+use std::collections::HashMap;
+
+use crate::RcInterner;
+
+/// A registry of named string interners
+///
+/// Each name refers to its own `RcInterner<str>`, which is created the first
+/// time a value is interned under that name. Values are deduplicated within a
+/// single named interner, but never across different names.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::InternerRegistry;
+///
+/// let mut registry = InternerRegistry::new();
+///
+/// let x = registry.intern("idents", "hello");
+/// let y = registry.intern("strings", "hello");
+///
+/// assert!(Rc::ptr_eq(&x, &registry.intern("idents", "hello")));
+/// assert!(!Rc::ptr_eq(&x, &y));
+/// ```
+#[derive(Debug, Default)]
+pub struct InternerRegistry(HashMap<String, RcInterner<str>>);
+
+impl InternerRegistry {
+    /// Create a new, empty registry.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::InternerRegistry;
+    /// let mut registry = InternerRegistry::new();
+    /// # let x = registry.intern("idents", "hello");
+    /// ```
+    pub fn new() -> InternerRegistry {
+        Default::default()
+    }
+
+    /// Intern a string slice into the interner with the given name
+    ///
+    /// If no interner with the given name exists yet, a new, empty one is
+    /// created first.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::InternerRegistry;
+    /// let mut registry = InternerRegistry::new();
+    ///
+    /// let x = registry.intern("idents", "hello");
+    /// let y = registry.intern("idents", "hello");
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern(&mut self, interner_name: &str, value: &str) -> Rc<str> {
+        self.interner(interner_name).intern_str(value)
+    }
+
+    /// Get a reference to the interner with the given name
+    ///
+    /// If no value has been interned under this name yet, `None` will be
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::InternerRegistry;
+    /// let mut registry = InternerRegistry::new();
+    ///
+    /// let x = registry.intern("idents", "hello");
+    ///
+    /// let idents = registry.get("idents").unwrap();
+    /// assert_eq!(idents.try_intern("hello"), Some(Rc::from("hello")));
+    /// assert!(registry.get("strings").is_none());
+    /// ```
+    pub fn get(&self, interner_name: &str) -> Option<&RcInterner<str>> {
+        self.0.get(interner_name)
+    }
+
+    /// Get a mutable reference to the interner with the given name, creating
+    /// it if it does not exist yet
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::InternerRegistry;
+    /// let mut registry = InternerRegistry::new();
+    ///
+    /// let x = registry.interner("idents").intern_string(String::from("hello"));
+    ///
+    /// assert_eq!(x, registry.intern("idents", "hello"));
+    /// ```
+    pub fn interner(&mut self, interner_name: &str) -> &mut RcInterner<str> {
+        if !self.0.contains_key(interner_name) {
+            self.0.insert(interner_name.to_owned(), RcInterner::new());
+        }
+
+        self.0.get_mut(interner_name).unwrap()
+    }
+
+    /// Deallocate all interned objects that are no longer referenced in every
+    /// named interner and shrink their internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::InternerRegistry;
+    /// let mut registry = InternerRegistry::new();
+    ///
+    /// let x = registry.intern("idents", "hello");
+    /// drop(x);
+    ///
+    /// registry.shrink_to_fit();
+    /// assert_eq!(registry.get("idents").unwrap().try_intern("hello"), None);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        for interner in self.0.values_mut() {
+            interner.shrink_to_fit();
+        }
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::InternerRegistry;
+    /// let mut registry = InternerRegistry::new();
+    ///
+    /// let x = registry.intern("idents", "hello");
+    /// let y = registry.intern("strings", "hello");
+    /// registry.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        for interner in self.0.values() {
+            interner.check_invariants();
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::rc::Rc;
+use std::hash::Hash;
+
+use crate::RcInterner;
+
+/// An interner for `Result<T, E>` values, interning `Ok` and `Err` values in
+/// two separate interners
+///
+/// Interning a `Result<T, E>` returns a `Result<Rc<T>, Rc<E>>`, where equal
+/// `Ok` values share one `Rc<T>` and equal `Err` values share one `Rc<E>`.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::ResultInterner;
+///
+/// let mut interner = ResultInterner::new();
+///
+/// let x = interner.intern(Ok::<_, u32>("hello"));
+/// let y = interner.intern(Err(404));
+///
+/// let z = interner.intern(Ok("hello"));
+/// let w = interner.intern(Err(404));
+///
+/// assert!(Rc::ptr_eq(&x.unwrap(), &z.unwrap()));
+/// assert!(Rc::ptr_eq(&y.unwrap_err(), &w.unwrap_err()));
+/// ```
+#[derive(Debug)]
+pub struct ResultInterner<T, E> {
+    ok: RcInterner<T>,
+    err: RcInterner<E>,
+}
+
+impl<T, E> Default for ResultInterner<T, E> {
+    fn default() -> ResultInterner<T, E> {
+        ResultInterner {
+            ok: Default::default(),
+            err: Default::default(),
+        }
+    }
+}
+
+impl<T: Hash + Eq, E: Hash + Eq> ResultInterner<T, E> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ResultInterner;
+    /// let mut interner = ResultInterner::new();
+    /// # let x = interner.intern(Ok::<_, u32>(42));
+    /// ```
+    pub fn new() -> ResultInterner<T, E> {
+        Default::default()
+    }
+
+    /// Intern an owned result
+    ///
+    /// `Ok` values are interned into the interner returned by `ok()`, `Err`
+    /// values are interned into the interner returned by `err()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::ResultInterner;
+    /// let mut interner = ResultInterner::new();
+    ///
+    /// let x = interner.intern(Ok(42));
+    /// let y = interner.intern(Err(42));
+    ///
+    /// assert_eq!(x, Ok(Rc::new(42)));
+    /// assert_eq!(y, Err(Rc::new(42)));
+    /// ```
+    pub fn intern(&mut self, t: Result<T, E>) -> Result<Rc<T>, Rc<E>> {
+        match t {
+            Ok(value) => Ok(self.ok.intern(value)),
+            Err(error) => Err(self.err.intern(error)),
+        }
+    }
+
+    /// Get a reference to the interner holding the `Ok` values
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::ResultInterner;
+    /// let mut interner = ResultInterner::new();
+    ///
+    /// let x = interner.intern(Ok::<_, u32>(42));
+    /// assert_eq!(interner.ok().try_intern(&42), Some(Rc::new(42)));
+    /// ```
+    pub fn ok(&self) -> &RcInterner<T> {
+        &self.ok
+    }
+
+    /// Get a reference to the interner holding the `Err` values
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ResultInterner;
+    /// let mut interner = ResultInterner::new();
+    ///
+    /// let x = interner.intern(Ok::<_, u32>(42));
+    /// assert_eq!(interner.err().try_intern(&42), None);
+    /// ```
+    pub fn err(&self) -> &RcInterner<E> {
+        &self.err
+    }
+
+    /// Deallocate all interned `Ok` and `Err` values that are no longer
+    /// referenced and shrink the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ResultInterner;
+    /// let mut interner = ResultInterner::new();
+    ///
+    /// let x = interner.intern(Ok::<_, u32>(42));
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.ok().try_intern(&42), None);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.ok.shrink_to_fit();
+        self.err.shrink_to_fit();
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::ResultInterner;
+    /// let mut interner = ResultInterner::new();
+    ///
+    /// let x = interner.intern(Ok::<_, u32>(42));
+    /// let y = interner.intern(Err::<u32, _>(1337));
+    /// interner.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        self.ok.check_invariants();
+        self.err.check_invariants();
+    }
+}
@@ -0,0 +1,54 @@
+//! Interner and handle types selected at build time
+//!
+//! With the `sync` feature enabled, `Interner<T>` is `ArcInterner<T>` and
+//! `Handle<T>` is `Arc<T>`. Otherwise, `Interner<T>` is `RcInterner<T>` and
+//! `Handle<T>` is `Rc<T>`. Code using these aliases can switch between
+//! single-threaded and thread-safe interning without changing call sites.
+//!
+//! All `intern_*()` methods are available on both interners, except for the
+//! methods named after the pointer type, like `intern_rc()` and
+//! `intern_arc()`.
+//!
+//! # Example
+//! ```rust
+//! use refcount_interner::selected::{Interner, Handle};
+//!
+//! let mut interner: Interner<str> = Interner::new();
+//!
+//! let x: Handle<str> = interner.intern_str("hello");
+//! let y = interner.intern_string(String::from("hello"));
+//!
+//! assert!(Handle::ptr_eq(&x, &y));
+//! assert_eq!(interner.try_intern("hello"), Some(x));
+//!
+//! #[cfg(feature = "sync")]
+//! std::thread::spawn(move || assert_eq!(y.as_ref(), "hello"))
+//!     .join()
+//!     .unwrap();
+//! ```
+
+#[cfg(feature = "sync")]
+use std::sync::Arc;
+#[cfg(not(feature = "sync"))]
+use std::rc::Rc;
+
+#[cfg(feature = "sync")]
+use crate::ArcInterner;
+#[cfg(not(feature = "sync"))]
+use crate::RcInterner;
+
+/// The interner selected by the `sync` feature
+#[cfg(feature = "sync")]
+pub type Interner<T> = ArcInterner<T>;
+
+/// The interner selected by the `sync` feature
+#[cfg(not(feature = "sync"))]
+pub type Interner<T> = RcInterner<T>;
+
+/// The handle type returned by the interner selected by the `sync` feature
+#[cfg(feature = "sync")]
+pub type Handle<T> = Arc<T>;
+
+/// The handle type returned by the interner selected by the `sync` feature
+#[cfg(not(feature = "sync"))]
+pub type Handle<T> = Rc<T>;
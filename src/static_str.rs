@@ -0,0 +1,230 @@
+use std::rc::Rc;
+use std::fmt;
+use std::ops::Deref;
+use std::hash::{Hash, Hasher};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+
+/// A handle to an interned string slice, which is either a `&'static str` or
+/// an `Rc<str>`
+///
+/// Returned by `StaticStrInterner`. It compares and hashes like `str`,
+/// regardless of which form it holds.
+#[derive(Debug, Clone)]
+pub enum StaticOrRc {
+    /// A string slice that lives for the whole program and was not copied.
+    Static(&'static str),
+    /// A string slice that was copied into a reference-counted allocation.
+    Rc(Rc<str>),
+}
+
+impl StaticOrRc {
+    /// Check whether two handles point to the same string slice in memory.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::StaticOrRc;
+    /// let x = StaticOrRc::Static("hello");
+    /// let y = StaticOrRc::Rc("hello".into());
+    ///
+    /// assert!(StaticOrRc::ptr_eq(&x, &x.clone()));
+    /// assert!(!StaticOrRc::ptr_eq(&x, &y));
+    /// ```
+    pub fn ptr_eq(this: &StaticOrRc, other: &StaticOrRc) -> bool {
+        std::ptr::eq(this.deref(), other.deref())
+    }
+}
+
+impl Deref for StaticOrRc {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            StaticOrRc::Static(s) => s,
+            StaticOrRc::Rc(s) => s,
+        }
+    }
+}
+
+impl AsRef<str> for StaticOrRc {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Borrow<str> for StaticOrRc {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq for StaticOrRc {
+    fn eq(&self, other: &StaticOrRc) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for StaticOrRc {}
+
+impl Hash for StaticOrRc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl fmt::Display for StaticOrRc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// A string interner that interns `&'static str` without copying
+///
+/// String slices interned with `intern_static()` are stored as they are,
+/// while other string slices are copied into an `Rc<str>`. Both forms are
+/// deduplicated against each other: whichever form of a string was interned
+/// first is returned for all later interns of an equal string.
+///
+/// Interned `Rc<str>` strings will be deallocated when there are no
+/// references to them any more and `shrink_to_fit()` is called on the
+/// interner.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::{StaticStrInterner, StaticOrRc};
+///
+/// let mut interner = StaticStrInterner::new();
+///
+/// let x = interner.intern_static("hello");
+/// let y = interner.intern_str(&String::from("hello"));
+///
+/// assert!(StaticOrRc::ptr_eq(&x, &y));
+/// assert!(matches!(y, StaticOrRc::Static(_)));
+/// ```
+#[derive(Debug, Default)]
+pub struct StaticStrInterner {
+    set: HashSet<StaticOrRc>,
+}
+
+impl StaticStrInterner {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::StaticStrInterner;
+    /// let mut interner = StaticStrInterner::new();
+    /// # let x = interner.intern_static("hello");
+    /// ```
+    pub fn new() -> StaticStrInterner {
+        Default::default()
+    }
+
+    /// Intern a static string slice without copying it
+    ///
+    /// If an equal string slice has already been interned, in either form, a
+    /// handle to the already interned string slice will be returned.
+    ///
+    /// If the string slice has not yet been interned, it is remembered as it
+    /// is, and a `StaticOrRc::Static` handle is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{StaticStrInterner, StaticOrRc};
+    /// let mut interner = StaticStrInterner::new();
+    ///
+    /// let x = interner.intern_str("hello");
+    /// let y = interner.intern_static("hello");
+    /// assert!(StaticOrRc::ptr_eq(&x, &y));
+    ///
+    /// let z = interner.intern_static("world");
+    /// let w = interner.intern_static("world");
+    /// assert!(matches!(z, StaticOrRc::Static(_)));
+    /// assert!(StaticOrRc::ptr_eq(&z, &w));
+    /// ```
+    pub fn intern_static(&mut self, s: &'static str) -> StaticOrRc {
+        self.intern_with(s, || StaticOrRc::Static(s))
+    }
+
+    /// Intern a string slice, copying it into an `Rc<str>` if it has not yet
+    /// been interned
+    ///
+    /// If an equal string slice has already been interned, in either form, a
+    /// handle to the already interned string slice will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{StaticStrInterner, StaticOrRc};
+    /// let mut interner = StaticStrInterner::new();
+    ///
+    /// let x = interner.intern_str(&String::from("hello"));
+    /// assert!(matches!(x, StaticOrRc::Rc(_)));
+    /// assert!(StaticOrRc::ptr_eq(&x, &interner.intern_str("hello")));
+    /// ```
+    pub fn intern_str(&mut self, s: &str) -> StaticOrRc {
+        self.intern_with(s, || StaticOrRc::Rc(Rc::from(s)))
+    }
+
+    fn intern_with<F>(&mut self, s: &str, make: F) -> StaticOrRc
+        where F: FnOnce() -> StaticOrRc
+    {
+        if let Some(value) = self.set.get(s) {
+            value.clone()
+        } else {
+            let value = make();
+            self.set.insert(value.clone());
+            value
+        }
+    }
+
+    /// Get the number of interned string slices.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::StaticStrInterner;
+    /// let mut interner = StaticStrInterner::new();
+    ///
+    /// interner.intern_static("hello");
+    /// interner.intern_str("hello");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Check whether no string slices are interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::StaticStrInterner;
+    /// let interner = StaticStrInterner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Deallocate all interned `Rc<str>` string slices that are no longer
+    /// referenced and shrink the internal storage to fit.
+    ///
+    /// Static string slices are never removed, since they do not need to be
+    /// deallocated.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::StaticStrInterner;
+    /// let mut interner = StaticStrInterner::new();
+    ///
+    /// drop(interner.intern_static("hello"));
+    /// drop(interner.intern_str("world"));
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.set.retain(|value| match value {
+            StaticOrRc::Static(_) => true,
+            StaticOrRc::Rc(value) => Rc::strong_count(value) > 1,
+        });
+        self.set.shrink_to_fit();
+    }
+}
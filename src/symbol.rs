@@ -0,0 +1,391 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::ops::Index;
+use std::convert::TryFrom;
+use std::collections::HashMap;
+
+/// A compact identifier for a string interned in a symbol interner
+///
+/// Symbols are dense indices starting from zero, assigned in the order the
+/// strings were first interned. A symbol is only meaningful together with the
+/// interner that returned it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Create a symbol from its numeric id.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::Symbol;
+    /// let symbol = Symbol::from_u32(42);
+    /// assert_eq!(symbol.as_u32(), 42);
+    /// ```
+    pub fn from_u32(id: u32) -> Symbol {
+        Symbol(id)
+    }
+
+    /// Get the numeric id of this symbol.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::CompactSymbolInterner;
+    /// let mut interner = CompactSymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    /// let y = interner.intern_symbol("world");
+    ///
+    /// assert_eq!(x.as_u32(), 0);
+    /// assert_eq!(y.as_u32(), 1);
+    /// ```
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    pub(crate) fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// An interner mapping strings to dense `Symbol` ids
+///
+/// Each distinct string is stored once as an `Rc<str>` and assigned the next
+/// free symbol id, starting from zero. Symbols stay valid for the lifetime of
+/// the interner, and can be resolved back to their string with `resolve()`.
+///
+/// Interners created from a `SharedSymbolSpace` agree on symbol ids: the same
+/// string gets the same symbol in every interner of the space.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::SymbolInterner;
+///
+/// let mut interner = SymbolInterner::new();
+///
+/// let x = interner.intern_symbol("hello");
+/// let y = interner.intern_symbol("world");
+///
+/// assert_eq!(x, interner.intern_symbol("hello"));
+/// assert_ne!(x, y);
+/// assert_eq!(interner.resolve(x), Some("hello"));
+/// ```
+#[derive(Debug, Default)]
+pub struct SymbolInterner {
+    symbols: Vec<Option<Rc<str>>>,
+    ids: HashMap<Rc<str>, Symbol>,
+    space: Option<SharedSymbolSpace>,
+}
+
+impl SymbolInterner {
+    /// Create a new, empty interner with its own symbol ids.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SymbolInterner;
+    /// let mut interner = SymbolInterner::new();
+    /// # let x = interner.intern_symbol("hello");
+    /// ```
+    pub fn new() -> SymbolInterner {
+        Default::default()
+    }
+
+    /// Intern a string slice, returning its symbol
+    ///
+    /// If the string slice has already been interned, the symbol of the
+    /// already interned string slice will be returned.
+    ///
+    /// If the string slice has not yet been interned, it is copied into an
+    /// `Rc<str>` and assigned a new symbol. For interners created from a
+    /// `SharedSymbolSpace`, the symbol is assigned by the space.
+    ///
+    /// # Panics
+    /// Panics if the number of symbols would exceed `u32::MAX`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SymbolInterner;
+    /// let mut interner = SymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    /// assert_eq!(x, interner.intern_symbol("hello"));
+    /// ```
+    pub fn intern_symbol(&mut self, s: &str) -> Symbol {
+        if let Some(symbol) = self.get(s) {
+            return symbol;
+        }
+
+        let (symbol, value) = match &self.space {
+            Some(space) => space.intern(s),
+            None => {
+                let id = u32::try_from(self.symbols.len())
+                    .expect("too many symbols");
+                (Symbol::from_u32(id), Rc::from(s))
+            }
+        };
+
+        if self.symbols.len() <= symbol.index() {
+            self.symbols.resize(symbol.index() + 1, None);
+        }
+        self.symbols[symbol.index()] = Some(value.clone());
+        self.ids.insert(value, symbol);
+        symbol
+    }
+
+    /// Attempt to get the symbol of an already interned string slice.
+    ///
+    /// If the string slice has not yet been interned into this interner,
+    /// `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SymbolInterner;
+    /// let mut interner = SymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    /// assert_eq!(interner.get("hello"), Some(x));
+    /// assert_eq!(interner.get("world"), None);
+    /// ```
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.ids.get(s).copied()
+    }
+
+    /// Merge all strings interned into `other` into this interner, returning
+    /// a table mapping the symbols of `other` to symbols of this interner
+    ///
+    /// The table is indexed by the numeric ids of the symbols of `other`.
+    /// Strings interned into both interners are mapped to the symbol they
+    /// already have in this interner. Ids that were not interned into `other`,
+    /// which only happens for interners of a `SharedSymbolSpace`, are mapped
+    /// to themselves.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{SymbolInterner, Symbol};
+    /// let mut a = SymbolInterner::new();
+    /// let hello = a.intern_symbol("hello");
+    /// a.intern_symbol("world");
+    ///
+    /// let mut b = SymbolInterner::new();
+    /// let foo = b.intern_symbol("foo");
+    /// let world = b.intern_symbol("world");
+    ///
+    /// let remap = a.merge(b);
+    ///
+    /// assert_eq!(a.len(), 3);
+    /// assert_eq!(&a[remap[foo.as_u32() as usize]], "foo");
+    /// assert_eq!(remap[world.as_u32() as usize], a.get("world").unwrap());
+    /// assert_eq!(a.get("hello"), Some(hello));
+    /// ```
+    pub fn merge(&mut self, other: SymbolInterner) -> Vec<Symbol> {
+        other.symbols.iter()
+            .enumerate()
+            .map(|(id, value)| match value {
+                Some(value) => self.intern_symbol(value),
+                None => Symbol::from_u32(id as u32),
+            })
+            .collect()
+    }
+
+    /// Get the string slice of a symbol
+    ///
+    /// If the symbol was not interned into this interner, `None` will be
+    /// returned.
+    ///
+    /// There are three ways to resolve a symbol: this method checks the
+    /// symbol and returns an option, indexing the interner with `interner[x]`
+    /// checks the symbol and panics if it is invalid, and the unsafe
+    /// `resolve_unchecked()` does not check the symbol at all.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{SymbolInterner, Symbol};
+    /// let mut interner = SymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    /// assert_eq!(interner.resolve(x), Some("hello"));
+    /// assert_eq!(&interner[x], "hello");
+    /// assert_eq!(interner.resolve(Symbol::from_u32(42)), None);
+    /// ```
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.symbols.get(symbol.index())?.as_deref()
+    }
+
+    /// Get the string slice of a symbol without checking the symbol
+    ///
+    /// This avoids the bounds check of `resolve()` in hot paths.
+    ///
+    /// # Safety
+    /// The symbol must have been returned by `intern_symbol()` or `get()` of
+    /// this interner. Resolving any other symbol is undefined behavior.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SymbolInterner;
+    /// let mut interner = SymbolInterner::new();
+    ///
+    /// let x = interner.intern_symbol("hello");
+    ///
+    /// // `x` was returned by this interner, so it can be resolved unchecked.
+    /// assert_eq!(unsafe { interner.resolve_unchecked(x) }, "hello");
+    /// ```
+    pub unsafe fn resolve_unchecked(&self, symbol: Symbol) -> &str {
+        match self.symbols.get_unchecked(symbol.index()) {
+            Some(value) => value,
+            None => std::hint::unreachable_unchecked(),
+        }
+    }
+
+    /// Get the number of strings interned into this interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SymbolInterner;
+    /// let mut interner = SymbolInterner::new();
+    ///
+    /// interner.intern_symbol("hello");
+    /// interner.intern_symbol("hello");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Check whether no strings have been interned into this interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SymbolInterner;
+    /// let interner = SymbolInterner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SharedSymbolSpace;
+    /// let space = SharedSymbolSpace::new();
+    /// let mut a = space.interner();
+    /// let mut b = space.interner();
+    ///
+    /// a.intern_symbol("hello");
+    /// b.intern_symbol("world");
+    /// a.check_invariants();
+    /// b.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        for (value, &symbol) in &self.ids {
+            let found = self.symbols.get(symbol.index())
+                .and_then(Option::as_ref)
+                .expect("interned string has no symbol");
+            assert!(Rc::ptr_eq(found, value), "wrong symbol");
+        }
+
+        let resolvable = self.symbols.iter().filter(|s| s.is_some()).count();
+        assert_eq!(resolvable, self.ids.len(), "symbol without string");
+        if self.space.is_none() {
+            assert_eq!(resolvable, self.symbols.len(), "symbol ids not dense");
+        }
+    }
+}
+
+/// A symbol id space shared between several `SymbolInterner`s
+///
+/// Every interner created with `interner()` gets its symbol ids from the
+/// space, so the same string gets the same symbol in all of them, no matter
+/// which interner interned it first. Each interner still only resolves the
+/// symbols interned into it.
+///
+/// Cloning a `SharedSymbolSpace` returns a handle to the same space.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::SharedSymbolSpace;
+///
+/// let space = SharedSymbolSpace::new();
+/// let mut a = space.interner();
+/// let mut b = space.interner();
+///
+/// let x = a.intern_symbol("hello");
+/// let y = b.intern_symbol("world");
+/// let z = b.intern_symbol("hello");
+///
+/// assert_eq!(x, z);
+/// assert_ne!(x, y);
+/// assert_eq!(a.resolve(x), b.resolve(z));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedSymbolSpace(Rc<RefCell<SymbolInterner>>);
+
+/// Get the string slice of a symbol
+///
+/// # Panics
+/// Panics if the symbol was not interned into this interner.
+///
+/// # Example
+/// ```rust
+/// # use refcount_interner::SymbolInterner;
+/// let mut interner = SymbolInterner::new();
+///
+/// let x = interner.intern_symbol("hello");
+/// assert_eq!(&interner[x], "hello");
+/// ```
+impl Index<Symbol> for SymbolInterner {
+    type Output = str;
+
+    fn index(&self, symbol: Symbol) -> &str {
+        self.resolve(symbol).expect("symbol not interned in this interner")
+    }
+}
+
+impl SharedSymbolSpace {
+    /// Create a new, empty symbol space.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SharedSymbolSpace;
+    /// let space = SharedSymbolSpace::new();
+    /// # let x = space.interner().intern_symbol("hello");
+    /// ```
+    pub fn new() -> SharedSymbolSpace {
+        Default::default()
+    }
+
+    /// Create a new, empty interner getting its symbol ids from this space.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::SharedSymbolSpace;
+    /// let space = SharedSymbolSpace::new();
+    ///
+    /// let mut a = space.interner();
+    /// let mut b = space.interner();
+    ///
+    /// a.intern_symbol("hello");
+    /// let x = b.intern_symbol("world");
+    ///
+    /// assert_eq!(x.as_u32(), 1);
+    /// assert_eq!(b.len(), 1);
+    /// ```
+    pub fn interner(&self) -> SymbolInterner {
+        SymbolInterner {
+            space: Some(self.clone()),
+            ..Default::default()
+        }
+    }
+
+    fn intern(&self, s: &str) -> (Symbol, Rc<str>) {
+        let mut interner = self.0.borrow_mut();
+        let symbol = interner.intern_symbol(s);
+        let value = interner.symbols[symbol.index()].clone()
+            .expect("symbol not interned in space");
+        (symbol, value)
+    }
+}
@@ -0,0 +1,130 @@
+use std::rc::Rc;
+use std::convert::TryFrom;
+use std::collections::HashMap;
+
+/// A string interner returning both an integer token and a shared handle
+///
+/// Each distinct string gets a `u32` token, assigned in the order the strings
+/// are first interned, and an `Rc<str>` handle. Tokens are compact enough to
+/// be stored in large arrays, while handles can be used directly without
+/// going through the interner. A token can be resolved back to its handle with
+/// `resolve_token()`.
+///
+/// Interned strings are never deallocated, so tokens stay valid for as long as
+/// the interner exists.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::TokenStrInterner;
+///
+/// let mut interner = TokenStrInterner::new();
+///
+/// let (x, hello) = interner.intern_token("hello");
+/// let (y, world) = interner.intern_token("world");
+///
+/// assert_ne!(x, y);
+/// assert_eq!(hello.as_ref(), "hello");
+/// assert!(Rc::ptr_eq(&interner.resolve_token(y).unwrap(), &world));
+/// ```
+#[derive(Debug, Default)]
+pub struct TokenStrInterner {
+    strings: Vec<Rc<str>>,
+    tokens: HashMap<Rc<str>, u32>,
+}
+
+impl TokenStrInterner {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::TokenStrInterner;
+    /// let mut interner = TokenStrInterner::new();
+    /// # let x = interner.intern_token("hello");
+    /// ```
+    pub fn new() -> TokenStrInterner {
+        Default::default()
+    }
+
+    /// Intern a string slice, returning its token and handle
+    ///
+    /// If the string slice has already been interned, the token and handle of
+    /// the already interned string slice will be returned.
+    ///
+    /// If the string slice has not yet been interned, it is cloned into an
+    /// `Rc<str>` and assigned the next token.
+    ///
+    /// # Panics
+    /// Panics if more than `u32::MAX` strings are interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::TokenStrInterner;
+    /// let mut interner = TokenStrInterner::new();
+    ///
+    /// let (x, a) = interner.intern_token("hello");
+    /// let (y, b) = interner.intern_token("hello");
+    ///
+    /// assert_eq!(x, y);
+    /// assert!(Rc::ptr_eq(&a, &b));
+    /// ```
+    pub fn intern_token(&mut self, s: &str) -> (u32, Rc<str>) {
+        if let Some((string, &token)) = self.tokens.get_key_value(s) {
+            return (token, string.clone());
+        }
+
+        let token = u32::try_from(self.strings.len())
+            .expect("too many tokens");
+        let string: Rc<str> = Rc::from(s);
+        self.strings.push(string.clone());
+        self.tokens.insert(string.clone(), token);
+        (token, string)
+    }
+
+    /// Get the handle of the string slice with the given token.
+    ///
+    /// If no string slice has been assigned this token, `None` will be
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::TokenStrInterner;
+    /// let mut interner = TokenStrInterner::new();
+    ///
+    /// let (x, hello) = interner.intern_token("hello");
+    /// assert!(Rc::ptr_eq(&interner.resolve_token(x).unwrap(), &hello));
+    /// assert_eq!(interner.resolve_token(x + 1), None);
+    /// ```
+    pub fn resolve_token(&self, token: u32) -> Option<Rc<str>> {
+        self.strings.get(token as usize).cloned()
+    }
+
+    /// Get the number of interned string slices.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::TokenStrInterner;
+    /// let mut interner = TokenStrInterner::new();
+    ///
+    /// interner.intern_token("hello");
+    /// interner.intern_token("hello");
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Check whether no string slices have been interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::TokenStrInterner;
+    /// let interner = TokenStrInterner::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use crate::RcInterner;
+
+/// An interner for token streams sharing equal tokens and equal streams
+///
+/// Every token is interned in a string interner, and the resulting slice of
+/// token handles is interned in a stream interner. Equal tokens therefore
+/// share one `Rc<str>`, and equal token streams share one `Rc<[Rc<str>]>`.
+///
+/// Like with `RcInterner`, interned tokens and streams will be deallocated
+/// when there are no references to them any more and `shrink_to_fit()` is
+/// called on the interner.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::TokenStreamInterner;
+///
+/// let mut interner = TokenStreamInterner::new();
+///
+/// let x = interner.intern_str_batch(&["let", "x", "=", "x"]);
+/// let y = interner.intern_str_batch(&["let", "y", "=", "x"]);
+///
+/// assert!(Rc::ptr_eq(&x[0], &y[0]));
+/// assert!(Rc::ptr_eq(&x[1], &x[3]));
+/// assert!(Rc::ptr_eq(&x[3], &y[3]));
+/// ```
+#[derive(Debug, Default)]
+pub struct TokenStreamInterner {
+    strings: RcInterner<str>,
+    streams: RcInterner<[Rc<str>]>,
+}
+
+impl TokenStreamInterner {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::TokenStreamInterner;
+    /// let mut interner = TokenStreamInterner::new();
+    /// # let x = interner.intern_str_batch(&["hello"]);
+    /// ```
+    pub fn new() -> TokenStreamInterner {
+        Default::default()
+    }
+
+    /// Intern a stream of tokens, returning the interned stream
+    ///
+    /// Each token is interned first, and the slice of interned tokens is
+    /// interned afterwards. If an equal stream has already been interned, a
+    /// reference to the already interned stream will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::TokenStreamInterner;
+    /// let mut interner = TokenStreamInterner::new();
+    ///
+    /// let x = interner.intern_str_batch(&["a", "+", "b"]);
+    /// let y = interner.intern_str_batch(&["a", "+", "b"]);
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// assert!(x.iter().zip(y.iter()).all(|(x, y)| Rc::ptr_eq(x, y)));
+    /// ```
+    pub fn intern_str_batch(&mut self, tokens: &[&str]) -> Rc<[Rc<str>]> {
+        let strings = &mut self.strings;
+        let tokens: Vec<Rc<str>> = tokens.iter()
+            .map(|token| strings.intern_str(token))
+            .collect();
+
+        self.streams.intern_vec(tokens)
+    }
+
+    /// Deallocate all interned streams and tokens that are no longer
+    /// referenced and shrink the internal storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::TokenStreamInterner;
+    /// let mut interner = TokenStreamInterner::new();
+    ///
+    /// let x = interner.intern_str_batch(&["a", "b"]);
+    /// let a = x[0].clone();
+    /// drop(x);
+    ///
+    /// interner.shrink_to_fit();
+    /// assert_eq!(Rc::strong_count(&a), 2);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.streams.shrink_to_fit();
+        self.strings.shrink_to_fit();
+    }
+}
@@ -0,0 +1,260 @@
+use std::rc::Rc;
+use std::hash::Hash;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// An interner returning reference-counted pointers to the interned data,
+/// whose entries expire after a fixed duration
+///
+/// Each entry remembers the time it was interned at. When
+/// `collect_expired()` is called, all entries older than the interner's TTL
+/// are removed, regardless of whether they are still referenced. Outstanding
+/// references to removed entries stay valid, but are no longer deduplicated
+/// against.
+///
+/// The current time is taken from a clock function, which is `Instant::now`
+/// by default and can be replaced with `with_clock()`.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// # use std::cell::Cell;
+/// # use std::time::{Duration, Instant};
+/// use refcount_interner::TtlRcInterner;
+///
+/// let now = Rc::new(Cell::new(Instant::now()));
+/// let clock = now.clone();
+/// let ttl = Duration::from_secs(60);
+/// let mut interner = TtlRcInterner::with_clock(ttl, move || clock.get());
+///
+/// let x = interner.intern(42);
+///
+/// now.set(now.get() + Duration::from_secs(30));
+/// interner.collect_expired();
+/// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+///
+/// now.set(now.get() + Duration::from_secs(30));
+/// interner.collect_expired();
+/// assert!(!Rc::ptr_eq(&x, &interner.intern(42)));
+/// ```
+#[derive(Debug)]
+pub struct TtlRcInterner<T: ?Sized, C = fn() -> Instant> {
+    entries: HashMap<Rc<T>, Instant>,
+    ttl: Duration,
+    clock: C,
+}
+
+impl<T: ?Sized + Hash + Eq> TtlRcInterner<T> {
+    /// Create a new, empty interner whose entries expire after `ttl`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use refcount_interner::TtlRcInterner;
+    /// let mut interner = TtlRcInterner::new(Duration::from_secs(60));
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new(ttl: Duration) -> TtlRcInterner<T> {
+        TtlRcInterner::with_clock(ttl, Instant::now)
+    }
+}
+
+impl<T: ?Sized + Hash + Eq, C: Fn() -> Instant> TtlRcInterner<T, C> {
+    /// Create a new, empty interner whose entries expire after `ttl`, using
+    /// `clock` to get the current time.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::time::{Duration, Instant};
+    /// # use refcount_interner::TtlRcInterner;
+    /// let start = Instant::now();
+    /// let ttl = Duration::from_secs(60);
+    /// let mut interner = TtlRcInterner::with_clock(ttl, move || start);
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn with_clock(ttl: Duration, clock: C) -> TtlRcInterner<T, C> {
+        TtlRcInterner {
+            entries: HashMap::new(),
+            ttl,
+            clock,
+        }
+    }
+
+    /// Attempt to get a reference to an already interned object.
+    ///
+    /// If the object has already been interned and has not been removed by
+    /// `collect_expired()`, an option containing a reference to the already
+    /// interned object will be returned.
+    ///
+    /// If the object has not yet been interned, `None` will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::time::Duration;
+    /// # use refcount_interner::TtlRcInterner;
+    /// let mut interner = TtlRcInterner::new(Duration::from_secs(60));
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(Rc::new(42)));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// ```
+    pub fn try_intern(&self, t: &T) -> Option<Rc<T>> {
+        self.entries.get_key_value(t).map(|(value, _)| value.clone())
+    }
+
+    /// Intern a boxed object
+    ///
+    /// If the object has already been interned, the passed object will be
+    /// dropped and deallocated, and a reference to the already interned object
+    /// will be returned. Its expiry time is not changed.
+    ///
+    /// If the object has not yet been interned, the passed object will be moved
+    /// into an `Rc<T>`, remembered together with the current time, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use refcount_interner::TtlRcInterner;
+    /// let mut interner = TtlRcInterner::new(Duration::from_secs(60));
+    ///
+    /// let x: Box<str> = Box::from("hello");
+    /// let y = interner.intern_boxed(x);
+    ///
+    /// assert_eq!(y.as_ref(), "hello");
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
+        if let Some(value) = self.try_intern(t.as_ref()) {
+            value
+        } else {
+            let value: Rc<T> = Rc::from(t);
+            self.entries.insert(value.clone(), (self.clock)());
+            value
+        }
+    }
+
+    /// Refresh the expiry time of an interned object
+    ///
+    /// The object will expire `ttl` after the time of this call. Returns
+    /// whether the object was interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::cell::Cell;
+    /// # use std::time::{Duration, Instant};
+    /// # use refcount_interner::TtlRcInterner;
+    /// let now = Rc::new(Cell::new(Instant::now()));
+    /// let clock = now.clone();
+    /// let ttl = Duration::from_secs(60);
+    /// let mut interner = TtlRcInterner::with_clock(ttl, move || clock.get());
+    ///
+    /// let x = interner.intern(42);
+    ///
+    /// now.set(now.get() + Duration::from_secs(30));
+    /// assert!(interner.touch(&42));
+    /// assert!(!interner.touch(&1337));
+    ///
+    /// now.set(now.get() + Duration::from_secs(30));
+    /// interner.collect_expired();
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn touch(&mut self, t: &T) -> bool {
+        let now = (self.clock)();
+        if let Some(interned_at) = self.entries.get_mut(t) {
+            *interned_at = now;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove all interned objects that have expired
+    ///
+    /// Expired objects are removed even if they are still referenced.
+    /// Outstanding references stay valid, but later calls to `intern()` will
+    /// allocate a new object.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use refcount_interner::TtlRcInterner;
+    /// let mut interner = TtlRcInterner::new(Duration::from_secs(0));
+    ///
+    /// let x = interner.intern(42);
+    ///
+    /// interner.collect_expired();
+    /// assert_eq!(interner.try_intern(&42), None);
+    /// assert_eq!(*x, 42);
+    /// ```
+    pub fn collect_expired(&mut self) {
+        let now = (self.clock)();
+        let ttl = self.ttl;
+        self.entries.retain(|_, interned_at| {
+            now.duration_since(*interned_at) < ttl
+        });
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use refcount_interner::TtlRcInterner;
+    /// let mut interner = TtlRcInterner::new(Duration::from_secs(0));
+    ///
+    /// let x = interner.intern(42);
+    /// interner.check_invariants();
+    ///
+    /// interner.collect_expired();
+    /// interner.check_invariants();
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        for value in self.entries.keys() {
+            let (found, _) = self.entries.get_key_value(value.as_ref())
+                .expect("interned object not found by lookup");
+            assert!(Rc::ptr_eq(found, value), "equal objects interned twice");
+        }
+    }
+}
+
+impl<T: Sized + Hash + Eq, C: Fn() -> Instant> TtlRcInterner<T, C> {
+    /// Intern an owned object
+    ///
+    /// If the object has already been interned, the passed object will be
+    /// dropped, and a reference to the already interned object will be
+    /// returned. Its expiry time is not changed.
+    ///
+    /// If the object has not yet been interned, the passed object will be moved
+    /// into an `Rc<T>`, remembered together with the current time, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use std::time::Duration;
+    /// # use refcount_interner::TtlRcInterner;
+    /// let mut interner = TtlRcInterner::new(Duration::from_secs(60));
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    ///
+    /// assert_eq!(*x, 42);
+    /// assert_ne!(x, y);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        if let Some(value) = self.try_intern(&t) {
+            value
+        } else {
+            let value = Rc::new(t);
+            self.entries.insert(value.clone(), (self.clock)());
+            value
+        }
+    }
+}
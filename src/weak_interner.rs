@@ -0,0 +1,537 @@
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+use std::hash::{Hash, BuildHasher};
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+
+use crate::metrics::WeakInternerStats;
+
+/// An interner returning reference-counted pointers to the interned data,
+/// which does not keep interned objects alive
+///
+/// Unlike `RcInterner`, this interner only stores `Weak<T>` handles to the
+/// interned objects, so interned objects are deallocated as soon as the last
+/// returned `Rc<T>` is dropped, without calling `shrink_to_fit()`. The entries
+/// of deallocated objects are removed whenever they are encountered by
+/// `intern()` or `try_intern()`, or all at once by `prune()`.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::WeakRcInterner;
+///
+/// let mut interner = WeakRcInterner::new();
+///
+/// let x = interner.intern(42);
+/// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+///
+/// drop(x);
+/// assert_eq!(interner.try_intern(&42), None);
+/// assert!(interner.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct WeakRcInterner<T: ?Sized> {
+    buckets: HashMap<u64, Vec<rc::Weak<T>>>,
+    hasher: RandomState,
+    stats: WeakInternerStats,
+}
+
+impl<T: ?Sized> Default for WeakRcInterner<T> {
+    fn default() -> WeakRcInterner<T> {
+        WeakRcInterner {
+            buckets: HashMap::new(),
+            hasher: RandomState::new(),
+            stats: WeakInternerStats::default(),
+        }
+    }
+}
+
+impl<T: ?Sized + Hash + Eq> WeakRcInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakRcInterner;
+    /// let mut interner = WeakRcInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> WeakRcInterner<T> {
+        Default::default()
+    }
+
+    fn find(&mut self, hash: u64, t: &T) -> Option<Rc<T>> {
+        let bucket = self.buckets.get_mut(&hash)?;
+        let mut found = None;
+        let mut dead = 0;
+        bucket.retain(|weak| match weak.upgrade() {
+            Some(value) => {
+                if found.is_none() && *value == *t {
+                    found = Some(value);
+                }
+                true
+            }
+            None => {
+                dead += 1;
+                false
+            }
+        });
+
+        self.stats.weak_dead += dead;
+        if bucket.is_empty() {
+            self.buckets.remove(&hash);
+        }
+        found
+    }
+
+    fn lookup(&mut self, hash: u64, t: &T) -> Option<Rc<T>> {
+        let found = self.find(hash, t);
+        if found.is_some() {
+            self.stats.weak_hits += 1;
+        } else {
+            self.stats.weak_misses += 1;
+        }
+        found
+    }
+
+    /// Attempt to get a reference to an already interned object.
+    ///
+    /// If the object has not yet been interned or has already been
+    /// deallocated, `None` will be returned. Entries of deallocated objects
+    /// with the same hash are removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakRcInterner;
+    /// let mut interner = WeakRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(x));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// ```
+    pub fn try_intern(&mut self, t: &T) -> Option<Rc<T>> {
+        let hash = self.hasher.hash_one(t);
+        self.lookup(hash, t)
+    }
+
+    /// Intern a boxed object
+    ///
+    /// If the object has already been interned and is still alive, the passed
+    /// object will be dropped and deallocated, and a reference to the already
+    /// interned object will be returned.
+    ///
+    /// Otherwise the passed object will be moved into an `Rc<T>`, remembered
+    /// for future calls to `intern()` for as long as it is alive, and
+    /// returned. Entries of deallocated objects with the same hash are removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::WeakRcInterner;
+    /// let mut interner = WeakRcInterner::new();
+    ///
+    /// let x = interner.intern_boxed(String::from("hello").into_boxed_str());
+    /// let y = interner.intern_boxed(String::from("hello").into_boxed_str());
+    ///
+    /// assert!(Rc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
+        let hash = self.hasher.hash_one(t.as_ref());
+        if let Some(value) = self.lookup(hash, t.as_ref()) {
+            return value;
+        }
+
+        let value: Rc<T> = Rc::from(t);
+        self.buckets.entry(hash)
+            .or_default()
+            .push(Rc::downgrade(&value));
+        value
+    }
+
+    /// Remove the entries of all deallocated objects and shrink the internal
+    /// storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakRcInterner;
+    /// let mut interner = WeakRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    /// drop(y);
+    /// assert_eq!(interner.len(), 2);
+    ///
+    /// interner.prune();
+    /// assert_eq!(interner.len(), 1);
+    /// assert_eq!(interner.stats().weak_dead(), 1);
+    /// ```
+    pub fn prune(&mut self) {
+        let mut dead = 0;
+        self.buckets.retain(|_, bucket| {
+            let len = bucket.len();
+            bucket.retain(|weak| weak.strong_count() > 0);
+            dead += (len - bucket.len()) as u64;
+            !bucket.is_empty()
+        });
+        self.stats.weak_dead += dead;
+        self.buckets.shrink_to_fit();
+    }
+
+    /// Get the counts of lookups and removed entries of deallocated objects.
+    ///
+    /// See `WeakInternerStats`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakRcInterner;
+    /// let mut interner = WeakRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// drop(x);
+    ///
+    /// let y = interner.intern(1337);
+    /// assert_eq!(interner.stats().weak_dead(), 0);
+    ///
+    /// let x = interner.intern(42);
+    /// let stats = interner.stats();
+    /// assert_eq!(stats.weak_dead(), 1);
+    /// assert_eq!(stats.weak_misses(), 3);
+    /// assert_eq!(stats.weak_hits(), 0);
+    ///
+    /// let z = interner.intern(1337);
+    /// assert_eq!(interner.stats().weak_hits(), 1);
+    /// ```
+    pub fn stats(&self) -> WeakInternerStats {
+        self.stats
+    }
+
+    /// Get the number of entries, including entries of deallocated objects
+    /// that have not been removed yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakRcInterner;
+    /// let mut interner = WeakRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(42);
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.buckets.values().map(Vec::len).sum()
+    }
+
+    /// Check whether there are no entries, including entries of deallocated
+    /// objects that have not been removed yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakRcInterner;
+    /// let interner = WeakRcInterner::<u32>::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+impl<T: Hash + Eq> WeakRcInterner<T> {
+    /// Intern an owned object
+    ///
+    /// If the object has already been interned and is still alive, the passed
+    /// object will be dropped, and a reference to the already interned object
+    /// will be returned.
+    ///
+    /// Otherwise the passed object will be moved into an `Rc<T>`, remembered
+    /// for future calls to `intern()` for as long as it is alive, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::WeakRcInterner;
+    /// let mut interner = WeakRcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    ///
+    /// assert_ne!(x, y);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        let hash = self.hasher.hash_one(&t);
+        if let Some(value) = self.lookup(hash, &t) {
+            return value;
+        }
+
+        let value = Rc::new(t);
+        self.buckets.entry(hash)
+            .or_default()
+            .push(Rc::downgrade(&value));
+        value
+    }
+}
+
+/// An interner returning atomically reference-counted pointers to the
+/// interned data, which does not keep interned objects alive
+///
+/// Unlike `ArcInterner`, this interner only stores `Weak<T>` handles to the
+/// interned objects, so interned objects are deallocated as soon as the last
+/// returned `Arc<T>` is dropped, without calling `shrink_to_fit()`. The entries
+/// of deallocated objects are removed whenever they are encountered by
+/// `intern()` or `try_intern()`, or all at once by `prune()`.
+///
+/// # Example
+/// ```rust
+/// # use std::sync::Arc;
+/// use refcount_interner::WeakArcInterner;
+///
+/// let mut interner = WeakArcInterner::new();
+///
+/// let x = interner.intern(42);
+/// assert!(Arc::ptr_eq(&x, &interner.intern(42)));
+///
+/// drop(x);
+/// assert_eq!(interner.try_intern(&42), None);
+/// assert!(interner.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct WeakArcInterner<T: ?Sized> {
+    buckets: HashMap<u64, Vec<sync::Weak<T>>>,
+    hasher: RandomState,
+    stats: WeakInternerStats,
+}
+
+impl<T: ?Sized> Default for WeakArcInterner<T> {
+    fn default() -> WeakArcInterner<T> {
+        WeakArcInterner {
+            buckets: HashMap::new(),
+            hasher: RandomState::new(),
+            stats: WeakInternerStats::default(),
+        }
+    }
+}
+
+impl<T: ?Sized + Hash + Eq> WeakArcInterner<T> {
+    /// Create a new, empty interner.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakArcInterner;
+    /// let mut interner = WeakArcInterner::new();
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn new() -> WeakArcInterner<T> {
+        Default::default()
+    }
+
+    fn find(&mut self, hash: u64, t: &T) -> Option<Arc<T>> {
+        let bucket = self.buckets.get_mut(&hash)?;
+        let mut found = None;
+        let mut dead = 0;
+        bucket.retain(|weak| match weak.upgrade() {
+            Some(value) => {
+                if found.is_none() && *value == *t {
+                    found = Some(value);
+                }
+                true
+            }
+            None => {
+                dead += 1;
+                false
+            }
+        });
+
+        self.stats.weak_dead += dead;
+        if bucket.is_empty() {
+            self.buckets.remove(&hash);
+        }
+        found
+    }
+
+    fn lookup(&mut self, hash: u64, t: &T) -> Option<Arc<T>> {
+        let found = self.find(hash, t);
+        if found.is_some() {
+            self.stats.weak_hits += 1;
+        } else {
+            self.stats.weak_misses += 1;
+        }
+        found
+    }
+
+    /// Attempt to get a reference to an already interned object.
+    ///
+    /// If the object has not yet been interned or has already been
+    /// deallocated, `None` will be returned. Entries of deallocated objects
+    /// with the same hash are removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakArcInterner;
+    /// let mut interner = WeakArcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(x));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// ```
+    pub fn try_intern(&mut self, t: &T) -> Option<Arc<T>> {
+        let hash = self.hasher.hash_one(t);
+        self.lookup(hash, t)
+    }
+
+    /// Intern a boxed object
+    ///
+    /// If the object has already been interned and is still alive, the passed
+    /// object will be dropped and deallocated, and a reference to the already
+    /// interned object will be returned.
+    ///
+    /// Otherwise the passed object will be moved into an `Arc<T>`, remembered
+    /// for future calls to `intern()` for as long as it is alive, and
+    /// returned. Entries of deallocated objects with the same hash are removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use refcount_interner::WeakArcInterner;
+    /// let mut interner = WeakArcInterner::new();
+    ///
+    /// let x = interner.intern_boxed(String::from("hello").into_boxed_str());
+    /// let y = interner.intern_boxed(String::from("hello").into_boxed_str());
+    ///
+    /// assert!(Arc::ptr_eq(&x, &y));
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Arc<T> {
+        let hash = self.hasher.hash_one(t.as_ref());
+        if let Some(value) = self.lookup(hash, t.as_ref()) {
+            return value;
+        }
+
+        let value: Arc<T> = Arc::from(t);
+        self.buckets.entry(hash)
+            .or_default()
+            .push(Arc::downgrade(&value));
+        value
+    }
+
+    /// Remove the entries of all deallocated objects and shrink the internal
+    /// storage to fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakArcInterner;
+    /// let mut interner = WeakArcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    /// drop(y);
+    /// assert_eq!(interner.len(), 2);
+    ///
+    /// interner.prune();
+    /// assert_eq!(interner.len(), 1);
+    /// assert_eq!(interner.stats().weak_dead(), 1);
+    /// ```
+    pub fn prune(&mut self) {
+        let mut dead = 0;
+        self.buckets.retain(|_, bucket| {
+            let len = bucket.len();
+            bucket.retain(|weak| weak.strong_count() > 0);
+            dead += (len - bucket.len()) as u64;
+            !bucket.is_empty()
+        });
+        self.stats.weak_dead += dead;
+        self.buckets.shrink_to_fit();
+    }
+
+    /// Get the counts of lookups and removed entries of deallocated objects.
+    ///
+    /// See `WeakInternerStats`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakArcInterner;
+    /// let mut interner = WeakArcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// drop(x);
+    ///
+    /// let y = interner.intern(1337);
+    /// assert_eq!(interner.stats().weak_dead(), 0);
+    ///
+    /// let x = interner.intern(42);
+    /// let stats = interner.stats();
+    /// assert_eq!(stats.weak_dead(), 1);
+    /// assert_eq!(stats.weak_misses(), 3);
+    /// assert_eq!(stats.weak_hits(), 0);
+    ///
+    /// let z = interner.intern(1337);
+    /// assert_eq!(interner.stats().weak_hits(), 1);
+    /// ```
+    pub fn stats(&self) -> WeakInternerStats {
+        self.stats
+    }
+
+    /// Get the number of entries, including entries of deallocated objects
+    /// that have not been removed yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakArcInterner;
+    /// let mut interner = WeakArcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(42);
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.buckets.values().map(Vec::len).sum()
+    }
+
+    /// Check whether there are no entries, including entries of deallocated
+    /// objects that have not been removed yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakArcInterner;
+    /// let interner = WeakArcInterner::<u32>::new();
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+impl<T: Hash + Eq> WeakArcInterner<T> {
+    /// Intern an owned object
+    ///
+    /// If the object has already been interned and is still alive, the passed
+    /// object will be dropped, and a reference to the already interned object
+    /// will be returned.
+    ///
+    /// Otherwise the passed object will be moved into an `Arc<T>`, remembered
+    /// for future calls to `intern()` for as long as it is alive, and
+    /// returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use refcount_interner::WeakArcInterner;
+    /// let mut interner = WeakArcInterner::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    ///
+    /// assert_ne!(x, y);
+    /// assert!(Arc::ptr_eq(&x, &interner.intern(42)));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Arc<T> {
+        let hash = self.hasher.hash_one(&t);
+        if let Some(value) = self.lookup(hash, &t) {
+            return value;
+        }
+
+        let value = Arc::new(t);
+        self.buckets.entry(hash)
+            .or_default()
+            .push(Arc::downgrade(&value));
+        value
+    }
+}
@@ -0,0 +1,202 @@
+use std::rc::{Rc, Weak};
+use std::hash::{Hash, BuildHasher};
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+
+/// A map keyed by interned objects, which does not keep its keys alive
+///
+/// Keys are inserted as `Rc<T>` handles returned by an `RcInterner`, but only
+/// `Weak<T>` handles to them are stored. Lookups use a plain `&T`. Once a key
+/// has been deallocated, for example by `RcInterner::shrink_to_fit()`, its
+/// entry is no longer found, and it is removed when its bucket is next
+/// modified or when `prune()` is called.
+///
+/// # Example
+/// ```rust
+/// use refcount_interner::{RcInterner, WeakKeyMap};
+///
+/// let mut interner = RcInterner::new();
+/// let mut types = WeakKeyMap::new();
+///
+/// let x = interner.intern_str("x");
+/// types.insert(&x, "int");
+/// assert_eq!(types.get("x"), Some(&"int"));
+///
+/// drop(x);
+/// interner.shrink_to_fit();
+/// assert_eq!(types.get("x"), None);
+/// ```
+#[derive(Debug)]
+pub struct WeakKeyMap<T: ?Sized, V> {
+    buckets: HashMap<u64, Vec<(Weak<T>, V)>>,
+    hasher: RandomState,
+}
+
+impl<T: ?Sized, V> Default for WeakKeyMap<T, V> {
+    fn default() -> WeakKeyMap<T, V> {
+        WeakKeyMap {
+            buckets: HashMap::new(),
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl<T: ?Sized + Hash + Eq, V> WeakKeyMap<T, V> {
+    /// Create a new, empty map.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakKeyMap;
+    /// let mut map = WeakKeyMap::<str, u32>::new();
+    /// ```
+    pub fn new() -> WeakKeyMap<T, V> {
+        Default::default()
+    }
+
+    /// Insert a value for an interned key, returning the previous value of
+    /// the key
+    ///
+    /// Entries with deallocated keys in the same bucket are removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, WeakKeyMap};
+    /// let mut interner = RcInterner::new();
+    /// let mut map = WeakKeyMap::new();
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(map.insert(&x, "a"), None);
+    /// assert_eq!(map.insert(&x, "b"), Some("a"));
+    /// ```
+    pub fn insert(&mut self, key: &Rc<T>, value: V) -> Option<V> {
+        let hash = self.hasher.hash_one(key.as_ref());
+        let bucket = self.buckets.entry(hash).or_default();
+        bucket.retain(|(weak, _)| weak.strong_count() > 0);
+
+        let found = bucket.iter_mut()
+            .find(|(weak, _)| matches(weak, key.as_ref()));
+        match found {
+            Some((_, old)) => Some(std::mem::replace(old, value)),
+            None => {
+                bucket.push((Rc::downgrade(key), value));
+                None
+            }
+        }
+    }
+
+    /// Get a reference to the value of a key.
+    ///
+    /// If the key has not been inserted or has been deallocated, `None` will
+    /// be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, WeakKeyMap};
+    /// let mut interner = RcInterner::new();
+    /// let mut map = WeakKeyMap::new();
+    ///
+    /// let x = interner.intern(42);
+    /// map.insert(&x, "a");
+    ///
+    /// assert_eq!(map.get(&42), Some(&"a"));
+    /// assert_eq!(map.get(&1337), None);
+    /// ```
+    pub fn get(&self, t: &T) -> Option<&V> {
+        self.buckets.get(&self.hasher.hash_one(t))?
+            .iter()
+            .find(|(weak, _)| matches(weak, t))
+            .map(|(_, value)| value)
+    }
+
+    /// Remove the entry of a key, returning its value
+    ///
+    /// Entries with deallocated keys in the same bucket are removed as well.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, WeakKeyMap};
+    /// let mut interner = RcInterner::new();
+    /// let mut map = WeakKeyMap::new();
+    ///
+    /// let x = interner.intern(42);
+    /// map.insert(&x, "a");
+    ///
+    /// assert_eq!(map.remove(&42), Some("a"));
+    /// assert_eq!(map.remove(&42), None);
+    /// ```
+    pub fn remove(&mut self, t: &T) -> Option<V> {
+        let hash = self.hasher.hash_one(t);
+        let bucket = self.buckets.get_mut(&hash)?;
+        bucket.retain(|(weak, _)| weak.strong_count() > 0);
+
+        let value = bucket.iter()
+            .position(|(weak, _)| matches(weak, t))
+            .map(|index| bucket.swap_remove(index).1);
+        if bucket.is_empty() {
+            self.buckets.remove(&hash);
+        }
+        value
+    }
+
+    /// Remove all entries whose keys have been deallocated.
+    ///
+    /// This should be called after `shrink_to_fit()` on the interner of the
+    /// keys, to free the memory used by dead entries.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, WeakKeyMap};
+    /// let mut interner = RcInterner::new();
+    /// let mut map = WeakKeyMap::new();
+    ///
+    /// let x = interner.intern(42);
+    /// let y = interner.intern(1337);
+    /// map.insert(&x, "a");
+    /// map.insert(&y, "b");
+    ///
+    /// drop(x);
+    /// interner.shrink_to_fit();
+    /// map.prune();
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn prune(&mut self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(|(weak, _)| weak.strong_count() > 0);
+            !bucket.is_empty()
+        });
+    }
+
+    /// Get the number of entries, including entries whose keys have been
+    /// deallocated but which have not yet been removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::{RcInterner, WeakKeyMap};
+    /// let mut interner = RcInterner::new();
+    /// let mut map = WeakKeyMap::new();
+    ///
+    /// let x = interner.intern(42);
+    /// map.insert(&x, "a");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.buckets.values().map(Vec::len).sum()
+    }
+
+    /// Check whether the map has no entries.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeakKeyMap;
+    /// let map = WeakKeyMap::<u32, ()>::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+/// Check whether a weak key is still alive and equal to `t`
+fn matches<T: ?Sized + Eq>(weak: &Weak<T>, t: &T) -> bool {
+    weak.upgrade().is_some_and(|key| *key == *t)
+}
@@ -0,0 +1,296 @@
+use std::rc::Rc;
+use std::mem;
+use std::hash::Hash;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+/// An interner returning reference-counted pointers to the interned data,
+/// which keeps the total size of the interned objects within a byte budget
+///
+/// Whenever interning a new object makes the total size of the interned
+/// objects exceed the budget, interned objects are evicted until it fits
+/// again. The least shared objects, i.e. those with the fewest references
+/// outside the interner, are evicted first, and among equally shared objects
+/// the least recently used one is evicted first.
+///
+/// Evicting an object only drops the reference held by the interner: handles
+/// to evicted objects stay valid, but objects interned afterwards are no
+/// longer deduplicated against them. Interning an object that exceeds the
+/// budget sorts all interned objects once to pick the objects to evict.
+///
+/// # Example
+/// ```rust
+/// # use std::rc::Rc;
+/// use refcount_interner::WeightedLruInterner;
+///
+/// let mut interner = WeightedLruInterner::with_budget(10);
+///
+/// let shared = interner.intern_str("shared");
+/// let clones = vec![shared.clone(), shared.clone()];
+/// let rare = interner.intern_str("rare");
+///
+/// let new = interner.intern_str("new");
+///
+/// assert!(interner.contains("shared"));
+/// assert!(!interner.contains("rare"));
+/// assert!(interner.bytes() <= 10);
+/// ```
+#[derive(Debug)]
+pub struct WeightedLruInterner<T: ?Sized> {
+    entries: HashMap<Rc<T>, Cell<u64>>,
+    clock: Cell<u64>,
+    budget: usize,
+    bytes: usize,
+}
+
+impl<T: ?Sized + Hash + Eq> WeightedLruInterner<T> {
+    /// Create a new, empty interner keeping the total size of the interned
+    /// objects within `budget` bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(1024);
+    /// assert_eq!(interner.budget(), 1024);
+    /// # let x = interner.intern(42);
+    /// ```
+    pub fn with_budget(budget: usize) -> WeightedLruInterner<T> {
+        WeightedLruInterner {
+            entries: HashMap::new(),
+            clock: Cell::new(0),
+            budget,
+            bytes: 0,
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+        now
+    }
+
+    fn evict(&mut self) {
+        if self.bytes <= self.budget {
+            return;
+        }
+
+        let mut victims: Vec<_> = self.entries.iter()
+            .map(|(value, used_at)| {
+                ((Rc::strong_count(value), used_at.get()), value.clone())
+            })
+            .collect();
+        victims.sort_unstable_by_key(|&(key, _)| key);
+
+        for (_, value) in victims {
+            if self.bytes <= self.budget {
+                break;
+            }
+
+            self.bytes -= mem::size_of_val(value.as_ref());
+            self.entries.remove(value.as_ref());
+        }
+    }
+
+    /// Attempt to get a reference to an already interned object, marking it
+    /// as the most recently used object.
+    ///
+    /// If the object has not yet been interned or has been evicted, `None`
+    /// will be returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(1024);
+    ///
+    /// let x = interner.intern(42);
+    /// assert_eq!(interner.try_intern(&42), Some(Rc::new(42)));
+    /// assert_eq!(interner.try_intern(&1337), None);
+    /// ```
+    pub fn try_intern(&self, t: &T) -> Option<Rc<T>> {
+        let (value, used_at) = self.entries.get_key_value(t)?;
+        used_at.set(self.tick());
+        Some(value.clone())
+    }
+
+    /// Intern a boxed object, marking it as the most recently used object
+    ///
+    /// See `RcInterner::intern_boxed()`. If the object has not yet been
+    /// interned and interning it exceeds the budget, interned objects are
+    /// evicted until the total size fits the budget again. This may evict the
+    /// newly interned object itself.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(4);
+    ///
+    /// let x: Box<str> = Box::from("hello");
+    /// let y = interner.intern_boxed(x);
+    ///
+    /// assert_eq!(y.as_ref(), "hello");
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn intern_boxed(&mut self, t: Box<T>) -> Rc<T> {
+        if let Some(value) = self.try_intern(t.as_ref()) {
+            value
+        } else {
+            let value: Rc<T> = Rc::from(t);
+            let used_at = Cell::new(self.tick());
+            self.bytes += mem::size_of_val(value.as_ref());
+            self.entries.insert(value.clone(), used_at);
+            self.evict();
+            value
+        }
+    }
+
+    /// Check whether an object is interned, without marking it as used.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(1024);
+    ///
+    /// let x = interner.intern(42);
+    /// assert!(interner.contains(&42));
+    /// assert!(!interner.contains(&1337));
+    /// ```
+    pub fn contains(&self, t: &T) -> bool {
+        self.entries.contains_key(t)
+    }
+
+    /// Get the budget for the total size of the interned objects in bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let interner = WeightedLruInterner::<str>::with_budget(1024);
+    /// assert_eq!(interner.budget(), 1024);
+    /// ```
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Get the total size of the interned objects in bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(1024);
+    ///
+    /// let x = interner.intern_str("hello");
+    /// let y = interner.intern_str("world");
+    /// assert_eq!(interner.bytes(), 10);
+    /// ```
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Get the number of interned objects.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(1024);
+    ///
+    /// interner.intern(42);
+    /// interner.intern(42);
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether no objects are interned.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let interner = WeightedLruInterner::<u32>::with_budget(1024);
+    /// assert!(interner.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Check the internal consistency of the interner, panicking if it is
+    /// violated.
+    ///
+    /// The total size of the interned objects must be the sum of their sizes,
+    /// and must not exceed the budget.
+    ///
+    /// Only available in tests and with the `debug-checks` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(64);
+    ///
+    /// let mut handles = Vec::new();
+    /// for i in 0..100 {
+    ///     let x = interner.intern_str(&i.to_string());
+    ///     if i % 3 == 0 {
+    ///         handles.push(x);
+    ///     }
+    ///     interner.check_invariants();
+    /// }
+    /// ```
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        let bytes: usize = self.entries.keys()
+            .map(|value| mem::size_of_val(value.as_ref()))
+            .sum();
+
+        assert_eq!(self.bytes, bytes, "total size out of sync");
+        assert!(self.bytes <= self.budget, "total size exceeds the budget");
+    }
+}
+
+impl<T: Hash + Eq> WeightedLruInterner<T> {
+    /// Intern an owned object, marking it as the most recently used object
+    ///
+    /// See `intern_boxed()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(8);
+    ///
+    /// let x = interner.intern(1u32);
+    /// let y = interner.intern(2u32);
+    /// assert!(Rc::ptr_eq(&x, &interner.intern(1)));
+    ///
+    /// // 2 is now the least recently used of two equally shared objects
+    /// let z = interner.intern(3u32);
+    /// assert!(interner.contains(&1));
+    /// assert!(!interner.contains(&2));
+    /// ```
+    pub fn intern(&mut self, t: T) -> Rc<T> {
+        self.intern_boxed(Box::new(t))
+    }
+}
+
+impl WeightedLruInterner<str> {
+    /// Intern a string slice, marking it as the most recently used object
+    ///
+    /// See `intern_boxed()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use std::rc::Rc;
+    /// # use refcount_interner::WeightedLruInterner;
+    /// let mut interner = WeightedLruInterner::with_budget(1024);
+    ///
+    /// let x = interner.intern_str("hello");
+    /// assert!(Rc::ptr_eq(&x, &interner.intern_str("hello")));
+    /// ```
+    pub fn intern_str(&mut self, t: &str) -> Rc<str> {
+        if let Some(value) = self.try_intern(t) {
+            value
+        } else {
+            self.intern_boxed(Box::from(t))
+        }
+    }
+}